@@ -1,6 +1,22 @@
 /// BIOS Software Interrupt (SWI) handler for Nintendo DS
 /// Implements ARM7 and ARM9 BIOS function calls
 
+/// Minimal register-file interface the BIOS needs (r0-r3, the registers the
+/// HLE'd SWI calls read arguments from and write results to).
+pub trait BiosRegisters {
+    fn get_r(&self, index: usize) -> u32;
+    fn set_r(&mut self, index: usize, value: u32);
+}
+
+/// Minimal memory-bus interface the BIOS needs for its block-copy/fill SWIs
+pub trait BiosBus {
+    fn read8(&self, addr: u32) -> u8;
+    fn read16(&self, addr: u32) -> u16;
+    fn read32(&self, addr: u32) -> u32;
+    fn write16(&mut self, addr: u32, value: u16);
+    fn write32(&mut self, addr: u32, value: u32);
+}
+
 /// BIOS handler for software interrupts
 pub struct BIOS {
     /// Internal state (for future use)
@@ -14,22 +30,27 @@ impl BIOS {
 
     /// Handle ARM7 software interrupt
     /// Processes SWI calls from ARM7 processor
-    pub fn swi7(&self, swi_num: u32) -> Result<i32, String> {
+    pub fn swi7(
+        &self,
+        swi_num: u32,
+        regs: &mut dyn BiosRegisters,
+        bus: &mut dyn BiosBus,
+    ) -> Result<i32, String> {
         match swi_num {
             0x00 => self.soft_reset(),
             0x01 => self.wait_by_loop(),
             0x02 => self.intr_wait(),
             0x03 => self.vsync_wait_by_loop(),
-            0x04 => self.cpu_set(),
-            0x05 => self.cpu_fast_set(),
+            0x04 => self.cpu_set(regs, bus),
+            0x05 => self.cpu_fast_set(regs, bus),
             0x06 => self.get_sine_table(),
             0x07 => self.get_pitch_table(),
             0x08 => self.get_volume_table(),
-            0x09 => self.sqrt(),
-            0x0A => self.calc_atan2(),
+            0x09 => self.sqrt(regs),
+            0x0A => self.calc_atan2(regs),
             0x0B => self.copy_5_over_3(),
             0x0C => self.divmod_and_remainder(),
-            0x0D => self.div(),
+            0x0D => self.div(regs),
             0x0E => self.mod_and_div(),
             0x0F => self.checksum(),
             0x10 => self.reset_memory(),
@@ -37,7 +58,7 @@ impl BIOS {
             0x12 => self.copy_memory(),
             0x13 => self.launch_boot(),
             0x14 => self.delay_loop(),
-            0x15 => self.get_crc16(),
+            0x15 => self.get_crc16(regs, bus),
             0x16 => self.is_debug_proc(),
             0x17 => self.get_sin_value(),
             0x18 => self.get_tan_value(),
@@ -54,22 +75,27 @@ impl BIOS {
 
     /// Handle ARM9 software interrupt
     /// Processes SWI calls from ARM9 processor
-    pub fn swi9(&self, swi_num: u32) -> Result<i32, String> {
+    pub fn swi9(
+        &self,
+        swi_num: u32,
+        regs: &mut dyn BiosRegisters,
+        bus: &mut dyn BiosBus,
+    ) -> Result<i32, String> {
         match swi_num {
             0x00 => self.soft_reset(),
             0x01 => self.wait_by_loop(),
             0x02 => self.intr_wait(),
             0x03 => self.vsync_wait_by_loop(),
-            0x04 => self.cpu_set(),
-            0x05 => self.cpu_fast_set(),
+            0x04 => self.cpu_set(regs, bus),
+            0x05 => self.cpu_fast_set(regs, bus),
             0x06 => self.get_sine_table(),
             0x07 => self.get_pitch_table(),
             0x08 => self.get_volume_table(),
-            0x09 => self.sqrt(),
-            0x0A => self.calc_atan2(),
+            0x09 => self.sqrt(regs),
+            0x0A => self.calc_atan2(regs),
             0x0B => self.copy_5_over_3(),
             0x0C => self.divmod_and_remainder(),
-            0x0D => self.div(),
+            0x0D => self.div(regs),
             0x0E => self.mod_and_div(),
             0x0F => self.checksum(),
             0x10 => self.reset_memory(),
@@ -77,7 +103,7 @@ impl BIOS {
             0x12 => self.copy_memory(),
             0x13 => self.launch_boot(),
             0x14 => self.delay_loop(),
-            0x15 => self.get_crc16(),
+            0x15 => self.get_crc16(regs, bus),
             0x16 => self.is_debug_proc(),
             0x17 => self.get_sin_value(),
             0x18 => self.get_tan_value(),
@@ -115,14 +141,63 @@ impl BIOS {
     }
 
     /// SWI 0x04: CPU set
-    /// Copy memory with CPU
-    fn cpu_set(&self) -> Result<i32, String> {
+    /// Copies or fills `r2 & 0x1FFFFF` units from r0 to r1. Bit 24 of r2
+    /// holds the fixed-source flag, bit 26 selects 32-bit (set) vs 16-bit units.
+    fn cpu_set(&self, regs: &mut dyn BiosRegisters, bus: &mut dyn BiosBus) -> Result<i32, String> {
+        let mut src = regs.get_r(0);
+        let mut dst = regs.get_r(1);
+        let control = regs.get_r(2);
+        let count = control & 0x1F_FFFF;
+        let fixed_source = (control & (1 << 24)) != 0;
+        let word_size = (control & (1 << 26)) != 0;
+
+        for _ in 0..count {
+            if word_size {
+                let value = bus.read32(src);
+                bus.write32(dst, value);
+                dst = dst.wrapping_add(4);
+                if !fixed_source {
+                    src = src.wrapping_add(4);
+                }
+            } else {
+                let value = bus.read16(src);
+                bus.write16(dst, value);
+                dst = dst.wrapping_add(2);
+                if !fixed_source {
+                    src = src.wrapping_add(2);
+                }
+            }
+        }
+
         Ok(0)
     }
 
     /// SWI 0x05: CPU fast set
-    /// Fast copy memory
-    fn cpu_fast_set(&self) -> Result<i32, String> {
+    /// Same semantics as CpuSet, but always moves 32-bit words in 8-word chunks.
+    fn cpu_fast_set(
+        &self,
+        regs: &mut dyn BiosRegisters,
+        bus: &mut dyn BiosBus,
+    ) -> Result<i32, String> {
+        let mut src = regs.get_r(0);
+        let mut dst = regs.get_r(1);
+        let control = regs.get_r(2);
+        let count = control & 0x1F_FFFF;
+        let fixed_source = (control & (1 << 24)) != 0;
+
+        let mut copied = 0u32;
+        while copied < count {
+            for _ in 0..8 {
+                let value = bus.read32(src);
+                bus.write32(dst, value);
+                dst = dst.wrapping_add(4);
+                if !fixed_source {
+                    src = src.wrapping_add(4);
+                }
+                copied += 1;
+            }
+        }
+
         Ok(0)
     }
 
@@ -142,12 +217,28 @@ impl BIOS {
     }
 
     /// SWI 0x09: Square root
-    fn sqrt(&self) -> Result<i32, String> {
+    /// Computes the integer square root of r0 into r0.
+    fn sqrt(&self, regs: &mut dyn BiosRegisters) -> Result<i32, String> {
+        let value = regs.get_r(0);
+        regs.set_r(0, Self::int_sqrt(value));
         Ok(0)
     }
 
     /// SWI 0x0A: Calculate arctangent 2
-    fn calc_atan2(&self) -> Result<i32, String> {
+    /// r0/r1 hold the X/Y components; the result (0..0x10000 representing a
+    /// full turn) is written back to r0.
+    fn calc_atan2(&self, regs: &mut dyn BiosRegisters) -> Result<i32, String> {
+        let x = regs.get_r(0) as i32 as f64;
+        let y = regs.get_r(1) as i32 as f64;
+        let angle = y.atan2(x);
+        let normalized = angle / (2.0 * std::f64::consts::PI);
+        let turns = if normalized < 0.0 {
+            normalized + 1.0
+        } else {
+            normalized
+        };
+        let result = ((turns * 0x10000 as f64) as i64 as u32) & 0xFFFF;
+        regs.set_r(0, result);
         Ok(0)
     }
 
@@ -162,7 +253,24 @@ impl BIOS {
     }
 
     /// SWI 0x0D: Division
-    fn div(&self) -> Result<i32, String> {
+    /// r0/r1 hold numerator/denominator; quotient goes to r0, remainder to
+    /// r1, and the absolute value of the quotient to r3.
+    fn div(&self, regs: &mut dyn BiosRegisters) -> Result<i32, String> {
+        let numerator = regs.get_r(0) as i32;
+        let denominator = regs.get_r(1) as i32;
+
+        if denominator == 0 {
+            regs.set_r(0, 0);
+            regs.set_r(1, 0);
+            regs.set_r(3, 0);
+            return Ok(0);
+        }
+
+        let quotient = numerator.wrapping_div(denominator);
+        let remainder = numerator.wrapping_rem(denominator);
+        regs.set_r(0, quotient as u32);
+        regs.set_r(1, remainder as u32);
+        regs.set_r(3, quotient.wrapping_abs() as u32);
         Ok(0)
     }
 
@@ -202,7 +310,27 @@ impl BIOS {
     }
 
     /// SWI 0x15: Get CRC16
-    fn get_crc16(&self) -> Result<i32, String> {
+    /// r0 is the seed, r1 the data address, r2 the length; result goes back to r0.
+    fn get_crc16(&self, regs: &mut dyn BiosRegisters, bus: &mut dyn BiosBus) -> Result<i32, String> {
+        let seed = regs.get_r(0) as u16;
+        let addr = regs.get_r(1);
+        let length = regs.get_r(2);
+
+        let mut acc = seed as u32;
+        for i in 0..length {
+            let byte = bus.read8(addr.wrapping_add(i));
+            acc ^= byte as u32;
+            for j in 0..8 {
+                if (acc & 1) != 0 {
+                    acc >>= 1;
+                    acc ^= (Self::CRC16_TABLE[j] as u32) << (7 - j);
+                } else {
+                    acc >>= 1;
+                }
+            }
+        }
+
+        regs.set_r(0, (acc & 0xFFFF) as u32);
         Ok(0)
     }
 
@@ -264,20 +392,55 @@ impl BIOS {
         0
     }
 
-    /// CRC16 calculation
-    pub fn crc16(data: &[u8]) -> u16 {
-        let mut crc = 0u16;
-        for byte in data {
-            crc = crc.wrapping_shl(8) ^ Self::crc16_table(((crc >> 8) ^ (*byte as u16)) & 0xFF);
+    /// Bit-by-bit integer square root, the same algorithm the DS BIOS uses
+    fn int_sqrt(value: u32) -> u32 {
+        if value == 0 {
+            return 0;
         }
-        crc
-    }
 
-    /// CRC16 lookup table
-    fn crc16_table(index: u16) -> u16 {
-        // Standard CRC16 table lookup
-        // For now, return 0
-        0
+        let mut remainder = value;
+        let mut result = 0u32;
+        let mut bit = 1u32 << 30;
+        while bit > remainder {
+            bit >>= 2;
+        }
+
+        while bit != 0 {
+            if remainder >= result + bit {
+                remainder -= result + bit;
+                result = (result >> 1) + bit;
+            } else {
+                result >>= 1;
+            }
+            bit >>= 2;
+        }
+
+        result
+    }
+
+    /// DS BIOS GetCRC16 table (the 8 constants used by the per-bit shift-xor loop)
+    const CRC16_TABLE: [u16; 8] = [
+        0xC0C1, 0xC181, 0xC301, 0xC601, 0xCC01, 0xD801, 0xF001, 0xA001,
+    ];
+
+    /// CRC16 calculation, matching the hardware GetCRC16 (SWI 0x15) routine
+    ///
+    /// `seed` is the caller's initial accumulator value (r0 on real hardware),
+    /// `data` is the byte range to checksum.
+    pub fn crc16(seed: u16, data: &[u8]) -> u16 {
+        let mut acc = seed as u32;
+        for &byte in data {
+            acc ^= byte as u32;
+            for j in 0..8 {
+                if (acc & 1) != 0 {
+                    acc >>= 1;
+                    acc ^= (Self::CRC16_TABLE[j] as u32) << (7 - j);
+                } else {
+                    acc >>= 1;
+                }
+            }
+        }
+        (acc & 0xFFFF) as u16
     }
 }
 
@@ -0,0 +1,154 @@
+/// Screenshot and frame-dump encoding
+///
+/// Keeps the 0xAARRGGBB -> encoder pixel layout conversion in one place so
+/// both a one-shot screenshot and the frame recorder below go through the
+/// same path. Built on the `image` crate; `Recorder` accumulates frames
+/// captured at `EmuWindow::draw_frame`'s capture point and flushes them as
+/// an animated GIF or a numbered PNG sequence once recording stops.
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, ImageBuffer, Rgba, RgbaImage};
+
+/// Convert one 0xAARRGGBB scanline buffer into an `image`-friendly RGBA
+/// buffer of the same dimensions
+fn to_rgba_image(buffer: &[u32], width: usize, height: usize) -> RgbaImage {
+    let mut image = ImageBuffer::new(width as u32, height as u32);
+    for (i, pixel) in buffer.iter().enumerate().take(width * height) {
+        let x = (i % width) as u32;
+        let y = (i / width) as u32;
+        let a = ((pixel >> 24) & 0xFF) as u8;
+        let r = ((pixel >> 16) & 0xFF) as u8;
+        let g = ((pixel >> 8) & 0xFF) as u8;
+        let b = (pixel & 0xFF) as u8;
+        image.put_pixel(x, y, Rgba([r, g, b, a]));
+    }
+    image
+}
+
+/// Stack the upper and lower screens into one 256x384 RGBA image
+fn stacked_image(upper: &[u32], lower: &[u32], width: usize, height: usize) -> RgbaImage {
+    let mut combined = ImageBuffer::new(width as u32, (height * 2) as u32);
+    let upper = to_rgba_image(upper, width, height);
+    let lower = to_rgba_image(lower, width, height);
+    for y in 0..height as u32 {
+        for x in 0..width as u32 {
+            combined.put_pixel(x, y, *upper.get_pixel(x, y));
+            combined.put_pixel(x, y + height as u32, *lower.get_pixel(x, y));
+        }
+    }
+    combined
+}
+
+/// Save the upper and lower screens stacked into a single PNG at `path`
+pub fn save_png_stacked(
+    upper: &[u32],
+    lower: &[u32],
+    width: usize,
+    height: usize,
+    path: &str,
+) -> Result<(), String> {
+    stacked_image(upper, lower, width, height)
+        .save(path)
+        .map_err(|e| e.to_string())
+}
+
+/// Save the upper and lower screens as two separate PNGs
+pub fn save_png_separate(
+    upper: &[u32],
+    lower: &[u32],
+    width: usize,
+    height: usize,
+    upper_path: &str,
+    lower_path: &str,
+) -> Result<(), String> {
+    to_rgba_image(upper, width, height)
+        .save(upper_path)
+        .map_err(|e| e.to_string())?;
+    to_rgba_image(lower, width, height)
+        .save(lower_path)
+        .map_err(|e| e.to_string())
+}
+
+/// How a recording session should be flushed once stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordingFormat {
+    /// One animated GIF of the stacked upper/lower screens
+    Gif,
+    /// One numbered PNG per captured frame, `<prefix>-00001.png` etc.
+    RawSequence,
+}
+
+/// Accumulates frames captured during `EmuWindow::draw_frame` and flushes
+/// them to disk once recording stops
+pub struct Recorder {
+    format: RecordingFormat,
+    width: usize,
+    height: usize,
+    frames: Vec<(Vec<u32>, Vec<u32>)>,
+    recording: bool,
+}
+
+impl Recorder {
+    pub fn new(width: usize, height: usize) -> Self {
+        Recorder {
+            format: RecordingFormat::Gif,
+            width,
+            height,
+            frames: Vec::new(),
+            recording: false,
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording
+    }
+
+    /// Begin accumulating frames, discarding anything captured previously
+    pub fn start(&mut self, format: RecordingFormat) {
+        self.format = format;
+        self.frames.clear();
+        self.recording = true;
+    }
+
+    /// Stop accumulating and flush the captured frames to `path`. For
+    /// `RawSequence`, `path` is used as a filename prefix.
+    pub fn stop_and_save(&mut self, path: &str) -> Result<(), String> {
+        self.recording = false;
+        let frames = std::mem::take(&mut self.frames);
+
+        match self.format {
+            RecordingFormat::Gif => self.save_gif(&frames, path),
+            RecordingFormat::RawSequence => self.save_raw_sequence(&frames, path),
+        }
+    }
+
+    /// Called from `EmuWindow::draw_frame` once per frame while recording
+    pub fn capture_frame(&mut self, upper: &[u32], lower: &[u32]) {
+        if self.recording {
+            self.frames.push((upper.to_vec(), lower.to_vec()));
+        }
+    }
+
+    fn save_gif(&self, frames: &[(Vec<u32>, Vec<u32>)], path: &str) -> Result<(), String> {
+        let file = std::fs::File::create(path).map_err(|e| e.to_string())?;
+        let mut encoder = GifEncoder::new(file);
+        encoder
+            .set_repeat(Repeat::Infinite)
+            .map_err(|e| e.to_string())?;
+
+        for (upper, lower) in frames {
+            let image = stacked_image(upper, lower, self.width, self.height);
+            let frame = Frame::from_parts(image, 0, 0, Delay::from_numer_denom_ms(1000, 60));
+            encoder.encode_frame(frame).map_err(|e| e.to_string())?;
+        }
+
+        Ok(())
+    }
+
+    fn save_raw_sequence(&self, frames: &[(Vec<u32>, Vec<u32>)], prefix: &str) -> Result<(), String> {
+        for (i, (upper, lower)) in frames.iter().enumerate() {
+            let path = format!("{}-{:05}.png", prefix, i + 1);
+            save_png_stacked(upper, lower, self.width, self.height, &path)?;
+        }
+        Ok(())
+    }
+}
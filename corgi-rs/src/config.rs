@@ -0,0 +1,157 @@
+/// Persistent emulator configuration
+///
+/// Holds everything a frontend would otherwise hard-code: the keyboard/
+/// gamepad binding table consumed by [`crate::input`] and a handful of
+/// run-time toggles (framelimiter, frameskip) that used to be TODOs in
+/// `emu_window.rs`. A single process-wide instance is reachable through
+/// [`Config::global`] so code far from `main` (e.g. `EmuThread::run`'s frame
+/// pacing) can read it without threading a reference through every call.
+use crate::input::InputAction;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+/// Process-wide configuration instance
+static CONFIG: OnceLock<Mutex<Config>> = OnceLock::new();
+
+/// Emulator configuration: input bindings plus run-time toggles
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Keyboard scancode -> action
+    pub key_bindings: HashMap<u32, InputAction>,
+    /// Gamepad button code (`gilrs::Button as u32`) -> action
+    pub gamepad_bindings: HashMap<u32, InputAction>,
+    /// Analog stick deadzone, in the 0.0..=1.0 range `gilrs` reports axes in
+    pub gamepad_deadzone: f32,
+    /// Honor the 60Hz frame pacing in `EmuThread::run`
+    pub enable_framelimiter: bool,
+    /// Frameskip toggle, flipped by `InputAction::ToggleFrameskip` and saved
+    /// with the rest of the config; nothing in the frame loop reads it, so
+    /// setting it skips no frames
+    pub frameskip: bool,
+}
+
+impl Config {
+    /// Default bindings: the keycode table `emu_window.rs` used to hard-code
+    fn with_default_bindings() -> Self {
+        let mut key_bindings = HashMap::new();
+        key_bindings.insert(38, InputAction::Key(crate::corgi_core::DSKey::Up));
+        key_bindings.insert(40, InputAction::Key(crate::corgi_core::DSKey::Down));
+        key_bindings.insert(37, InputAction::Key(crate::corgi_core::DSKey::Left));
+        key_bindings.insert(39, InputAction::Key(crate::corgi_core::DSKey::Right));
+        key_bindings.insert(81, InputAction::Key(crate::corgi_core::DSKey::L)); // Q
+        key_bindings.insert(87, InputAction::Key(crate::corgi_core::DSKey::R)); // W
+        key_bindings.insert(65, InputAction::Key(crate::corgi_core::DSKey::Y)); // A
+        key_bindings.insert(83, InputAction::Key(crate::corgi_core::DSKey::X)); // S
+        key_bindings.insert(88, InputAction::Key(crate::corgi_core::DSKey::A)); // X
+        key_bindings.insert(90, InputAction::Key(crate::corgi_core::DSKey::B)); // Z
+        key_bindings.insert(13, InputAction::Key(crate::corgi_core::DSKey::Start));
+        key_bindings.insert(32, InputAction::Key(crate::corgi_core::DSKey::Select));
+        key_bindings.insert(48, InputAction::Key(crate::corgi_core::DSKey::Debugging));
+        key_bindings.insert(9, InputAction::ToggleFramelimiter); // Tab
+        key_bindings.insert(79, InputAction::ToggleFrameskip); // O
+        key_bindings.insert(80, InputAction::Pause); // P
+        key_bindings.insert(123, InputAction::Screenshot); // F12
+
+        let mut gamepad_bindings = HashMap::new();
+        gamepad_bindings.insert(0, InputAction::Key(crate::corgi_core::DSKey::A));
+        gamepad_bindings.insert(1, InputAction::Key(crate::corgi_core::DSKey::B));
+        gamepad_bindings.insert(2, InputAction::Key(crate::corgi_core::DSKey::Y));
+        gamepad_bindings.insert(3, InputAction::Key(crate::corgi_core::DSKey::X));
+        gamepad_bindings.insert(4, InputAction::Key(crate::corgi_core::DSKey::L));
+        gamepad_bindings.insert(5, InputAction::Key(crate::corgi_core::DSKey::R));
+        gamepad_bindings.insert(6, InputAction::Key(crate::corgi_core::DSKey::Select));
+        gamepad_bindings.insert(7, InputAction::Key(crate::corgi_core::DSKey::Start));
+        gamepad_bindings.insert(11, InputAction::Key(crate::corgi_core::DSKey::Up));
+        gamepad_bindings.insert(12, InputAction::Key(crate::corgi_core::DSKey::Down));
+        gamepad_bindings.insert(13, InputAction::Key(crate::corgi_core::DSKey::Left));
+        gamepad_bindings.insert(14, InputAction::Key(crate::corgi_core::DSKey::Right));
+
+        Config {
+            key_bindings,
+            gamepad_bindings,
+            gamepad_deadzone: 0.2,
+            enable_framelimiter: true,
+            frameskip: false,
+        }
+    }
+
+    /// Access the process-wide configuration instance, initializing it with
+    /// default bindings on first use
+    pub fn global() -> &'static Mutex<Config> {
+        CONFIG.get_or_init(|| Mutex::new(Config::with_default_bindings()))
+    }
+
+    /// Convenience accessor mirroring `Config::global().lock().unwrap()`
+    pub fn lock() -> MutexGuard<'static, Config> {
+        Config::global().lock().unwrap()
+    }
+
+    /// Load bindings and toggles from a simple `key = value` text file,
+    /// falling back to the default bindings for anything not covered
+    pub fn load(path: &str) -> Result<Config, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let mut config = Config::with_default_bindings();
+
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let key = key.trim();
+            let value = value.trim();
+
+            match key {
+                "enable_framelimiter" => config.enable_framelimiter = value == "true",
+                "frameskip" => config.frameskip = value == "true",
+                "gamepad_deadzone" => {
+                    if let Ok(v) = value.parse() {
+                        config.gamepad_deadzone = v;
+                    }
+                }
+                _ => {
+                    if let Some(code) = key.strip_prefix("key.").and_then(|c| c.parse().ok()) {
+                        if let Some(action) = InputAction::from_str(value) {
+                            config.key_bindings.insert(code, action);
+                        }
+                    } else if let Some(code) =
+                        key.strip_prefix("gamepad.").and_then(|c| c.parse().ok())
+                    {
+                        if let Some(action) = InputAction::from_str(value) {
+                            config.gamepad_bindings.insert(code, action);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Save bindings and toggles to a `key = value` text file `load` can
+    /// round-trip
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let mut text = String::new();
+        let _ = writeln!(text, "enable_framelimiter = {}", self.enable_framelimiter);
+        let _ = writeln!(text, "frameskip = {}", self.frameskip);
+        let _ = writeln!(text, "gamepad_deadzone = {}", self.gamepad_deadzone);
+
+        for (code, action) in &self.key_bindings {
+            let _ = writeln!(text, "key.{} = {}", code, action.as_str());
+        }
+        for (code, action) in &self.gamepad_bindings {
+            let _ = writeln!(text, "gamepad.{} = {}", code, action.as_str());
+        }
+
+        std::fs::write(path, text).map_err(|e| e.to_string())
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config::with_default_bindings()
+    }
+}
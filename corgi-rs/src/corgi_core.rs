@@ -0,0 +1,155 @@
+/// Headless core facade over `Emulator`
+///
+/// `CorgiCore` has no GUI toolkit or stdout dependencies: a frontend drives
+/// it by calling `run_frame()` on whatever schedule suits it and pulling the
+/// result via `copy_framebuffers`. This is what lets the same emulator core
+/// be embedded in multiple frontends (a druid `gui`-feature window, an
+/// automated test, a libretro core, a remote server) without any of them
+/// depending on each other.
+use crate::memconsts::{PIXELS_PER_LINE, SCANLINES};
+
+/// DS key codes, independent of any particular frontend's keymap
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DSKey {
+    Up,
+    Down,
+    Left,
+    Right,
+    A,
+    B,
+    X,
+    Y,
+    L,
+    R,
+    Start,
+    Select,
+    Debugging,
+}
+
+/// Headless emulator core: owns the `Emulator`, the latest frame, and
+/// nothing else
+pub struct CorgiCore {
+    emulator: crate::emulator::Emulator,
+    rom_loaded: bool,
+    upper_buffer: Vec<u32>,
+    lower_buffer: Vec<u32>,
+}
+
+impl CorgiCore {
+    pub fn new() -> Self {
+        let pixels = PIXELS_PER_LINE as usize * SCANLINES as usize;
+        CorgiCore {
+            emulator: crate::emulator::Emulator::new(),
+            rom_loaded: false,
+            upper_buffer: vec![0u32; pixels],
+            lower_buffer: vec![0u32; pixels],
+        }
+    }
+
+    /// Power on the emulator core (BIOS/firmware init, no ROM yet)
+    pub fn init(&mut self) -> Result<(), String> {
+        self.emulator.init()
+    }
+
+    /// Load the DS firmware image required before a ROM can run
+    pub fn load_firmware(&mut self) -> Result<(), String> {
+        self.emulator.load_firmware()
+    }
+
+    /// Load a ROM file and mark the core ready to step frames
+    pub fn load_rom(&mut self, path: &str) -> Result<(), String> {
+        self.emulator.load_rom(path)?;
+        self.rom_loaded = true;
+        Ok(())
+    }
+
+    /// Whether a ROM has successfully been loaded
+    pub fn is_rom_loaded(&self) -> bool {
+        self.rom_loaded
+    }
+
+    /// Step the emulator by exactly one frame, latching the resulting
+    /// framebuffers for `copy_framebuffers`
+    pub fn run_frame(&mut self) -> Result<(), String> {
+        self.emulator.run()?;
+
+        let upper = self.emulator.get_upper_frame();
+        let len = self.upper_buffer.len().min(upper.len());
+        self.upper_buffer[..len].copy_from_slice(&upper[..len]);
+
+        let lower = self.emulator.get_lower_frame();
+        let len = self.lower_buffer.len().min(lower.len());
+        self.lower_buffer[..len].copy_from_slice(&lower[..len]);
+
+        Ok(())
+    }
+
+    /// Copy the most recently rendered frame into caller-owned buffers
+    pub fn copy_framebuffers(&self, upper: &mut [u32], lower: &mut [u32]) {
+        let len = upper.len().min(self.upper_buffer.len());
+        upper[..len].copy_from_slice(&self.upper_buffer[..len]);
+
+        let len = lower.len().min(self.lower_buffer.len());
+        lower[..len].copy_from_slice(&self.lower_buffer[..len]);
+    }
+
+    /// Press or release one DS button
+    pub fn set_key(&mut self, key: DSKey, pressed: bool) {
+        match (key, pressed) {
+            (DSKey::Up, true) => self.emulator.button_up_pressed(),
+            (DSKey::Up, false) => self.emulator.button_up_released(),
+            (DSKey::Down, true) => self.emulator.button_down_pressed(),
+            (DSKey::Down, false) => self.emulator.button_down_released(),
+            (DSKey::Left, true) => self.emulator.button_left_pressed(),
+            (DSKey::Left, false) => self.emulator.button_left_released(),
+            (DSKey::Right, true) => self.emulator.button_right_pressed(),
+            (DSKey::Right, false) => self.emulator.button_right_released(),
+            (DSKey::A, true) => self.emulator.button_a_pressed(),
+            (DSKey::A, false) => self.emulator.button_a_released(),
+            (DSKey::B, true) => self.emulator.button_b_pressed(),
+            (DSKey::B, false) => self.emulator.button_b_released(),
+            (DSKey::X, true) => self.emulator.button_x_pressed(),
+            (DSKey::X, false) => self.emulator.button_x_released(),
+            (DSKey::Y, true) => self.emulator.button_y_pressed(),
+            (DSKey::Y, false) => self.emulator.button_y_released(),
+            (DSKey::L, true) => self.emulator.button_l_pressed(),
+            (DSKey::L, false) => self.emulator.button_l_released(),
+            (DSKey::R, true) => self.emulator.button_r_pressed(),
+            (DSKey::R, false) => self.emulator.button_r_released(),
+            (DSKey::Start, true) => self.emulator.button_start_pressed(),
+            (DSKey::Start, false) => self.emulator.button_start_released(),
+            (DSKey::Select, true) => self.emulator.button_select_pressed(),
+            (DSKey::Select, false) => self.emulator.button_select_released(),
+            (DSKey::Debugging, true) => {
+                let _ = self.emulator.debug();
+            }
+            (DSKey::Debugging, false) => {}
+        }
+    }
+
+    /// Report a touchscreen press at the given lower-screen pixel coordinates
+    pub fn touch(&mut self, x: i32, y: i32) {
+        let _ = self.emulator.touchscreen_press(x, y);
+    }
+
+    /// Report the touchscreen being released
+    pub fn release_touch(&mut self) {
+        self.emulator.touchscreen_release();
+    }
+
+    /// Write a savestate to `path`
+    pub fn save_state(&self, path: &str) -> Result<(), String> {
+        self.emulator.save_state(path)
+    }
+
+    /// Load a savestate from `path`
+    pub fn load_state(&mut self, path: &str) -> Result<(), String> {
+        self.emulator.load_state(path)
+    }
+}
+
+impl Default for CorgiCore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -2,36 +2,18 @@
 /// Copyright PSISP 2017
 /// Licensed under the GPLv3
 /// See LICENSE.txt for details
-use druid::widget::{Button, Container, Flex, Image, Label};
-use druid::{
-    im::Vector, AppLauncher, Color, Data, Env, Event, EventCtx, LayoutCtx, LocalizedString,
-    PaintCtx, Size, UnitData, UpdateCtx, Widget, WidgetExt, WindowDesc,
-};
+///
+/// Thin druid-facing adapter over the GUI-free `CorgiCore`. Only compiled
+/// under the `gui` Cargo feature, so the core stays embeddable in frontends
+/// that don't want a druid dependency.
+use crate::capture::{Recorder, RecordingFormat};
+use crate::config::Config;
+use crate::corgi_core::{CorgiCore, DSKey};
+use crate::input::{InputAction, InputManager};
+use crate::touch_input::{OnScreenControls, ScreenLayout, TouchMapper};
+use druid::Data;
 use std::sync::{Arc, Mutex};
 
-/// DS key codes enumeration
-#[derive(Clone, Copy, Debug, PartialEq, Data)]
-pub enum DSKey {
-    /// D-Pad keys
-    ButtonUp = 0,
-    ButtonDown = 1,
-    ButtonLeft = 2,
-    ButtonRight = 3,
-    /// Face buttons
-    ButtonA = 4,
-    ButtonB = 5,
-    ButtonX = 6,
-    ButtonY = 7,
-    /// Shoulder buttons
-    ButtonL = 8,
-    ButtonR = 9,
-    /// System buttons
-    ButtonStart = 10,
-    ButtonSelect = 11,
-    /// Debug key
-    Debugging = 12,
-}
-
 /// Screen dimensions constants
 pub const PIXELS_PER_LINE: usize = 256;
 pub const SCANLINES: usize = 192;
@@ -45,79 +27,46 @@ pub enum PauseEvent {
     LoadingRom,
 }
 
-/// Frame buffer data
-#[derive(Clone)]
-pub struct FrameBuffer {
-    /// Upper screen buffer (256x192 RGBA)
-    upper: Vec<u32>,
-    /// Lower screen buffer (256x192 RGBA)
-    lower: Vec<u32>,
-}
-
-impl FrameBuffer {
-    /// Create new empty frame buffer
-    pub fn new() -> Self {
-        FrameBuffer {
-            upper: vec![0; PIXELS_PER_LINE * SCANLINES],
-            lower: vec![0; PIXELS_PER_LINE * SCANLINES],
-        }
-    }
-
-    /// Update upper screen buffer
-    pub fn update_upper(&mut self, buffer: &[u32]) {
-        if buffer.len() == self.upper.len() {
-            self.upper.copy_from_slice(buffer);
-        }
-    }
-
-    /// Update lower screen buffer
-    pub fn update_lower(&mut self, buffer: &[u32]) {
-        if buffer.len() == self.lower.len() {
-            self.lower.copy_from_slice(buffer);
-        }
-    }
-}
-
-/// Main emulator window state
+/// Main emulator window state: owns a `CorgiCore` and adapts druid
+/// input/paint events to it
 #[derive(Clone, Data)]
 pub struct EmuWindow {
+    #[data(ignore)]
+    core: Arc<Mutex<CorgiCore>>,
+    #[data(ignore)]
+    input: Arc<Mutex<InputManager>>,
+    #[data(ignore)]
+    recorder: Arc<Mutex<Recorder>>,
+    #[data(ignore)]
+    touch_mapper: TouchMapper,
+    #[data(ignore)]
+    on_screen_controls: Option<OnScreenControls>,
     /// Current ROM file name
     pub rom_file_name: String,
-    /// Frame buffer data
-    pub frame_buffer: Arc<Mutex<FrameBuffer>>,
     /// Current FPS counter
     pub fps: u32,
     /// Is emulation running
     pub is_running: bool,
-    /// Is currently emulating a game
-    pub is_emulating: bool,
-    /// Configuration state
-    pub enable_framelimiter: bool,
-    pub frameskip: bool,
 }
 
 impl EmuWindow {
     /// Create new emulator window
     pub fn new() -> Self {
         EmuWindow {
+            core: Arc::new(Mutex::new(CorgiCore::new())),
+            input: Arc::new(Mutex::new(InputManager::new())),
+            recorder: Arc::new(Mutex::new(Recorder::new(PIXELS_PER_LINE, SCANLINES))),
+            touch_mapper: TouchMapper::default(),
+            on_screen_controls: None,
             rom_file_name: String::new(),
-            frame_buffer: Arc::new(Mutex::new(FrameBuffer::new())),
             fps: 0,
             is_running: false,
-            is_emulating: false,
-            enable_framelimiter: true,
-            frameskip: false,
         }
     }
 
-    /// Initialize the emulator window
+    /// Initialize the emulator core
     pub fn initialize(&self) -> Result<(), String> {
-        /// Initialize emulation thread
-        // TODO: Initialize EmuThread equivalent
-
-        /// Set window title
-        println!("CorgiDS initialized successfully");
-        Ok(())
+        self.core.lock().unwrap().init()
     }
 
     /// Check if emulator is running
@@ -125,9 +74,9 @@ impl EmuWindow {
         self.is_running
     }
 
-    /// Check if game is being emulated
+    /// Check if a ROM is loaded and being emulated
     pub fn is_emulating(&self) -> bool {
-        self.is_emulating
+        self.core.lock().unwrap().is_rom_loaded()
     }
 
     /// Check if frame finished rendering
@@ -135,105 +84,197 @@ impl EmuWindow {
         true // TODO: Implement frame sync
     }
 
-    /// Update frame buffers with new data
-    pub fn draw_frame(&mut self, upper_buffer: &[u32], lower_buffer: &[u32]) {
-        if let Ok(mut fb) = self.frame_buffer.lock() {
-            fb.update_upper(upper_buffer);
-            fb.update_lower(lower_buffer);
-        }
+    /// Step one emulated frame and copy the result into `upper`/`lower`.
+    /// This is also the capture point for an in-progress recording.
+    pub fn draw_frame(&mut self, upper_buffer: &mut [u32], lower_buffer: &mut [u32]) -> Result<(), String> {
+        let mut core = self.core.lock().unwrap();
+        core.run_frame()?;
+        core.copy_framebuffers(upper_buffer, lower_buffer);
+        self.recorder.lock().unwrap().capture_frame(upper_buffer, lower_buffer);
+        Ok(())
     }
 
-    /// Update FPS display
+    /// Update FPS counter
     pub fn update_fps(&mut self, fps: u32) {
         self.fps = fps;
-        println!("CorgiDS - {} FPS", fps);
-    }
-
-    /// Handle key press event
-    pub fn handle_key_press(&self, key_code: u32) -> Option<DSKey> {
-        /// Map keyboard codes to DS keys
-        match key_code {
-            // Arrow keys
-            38 => Some(DSKey::ButtonUp),    // Up arrow
-            40 => Some(DSKey::ButtonDown),  // Down arrow
-            37 => Some(DSKey::ButtonLeft),  // Left arrow
-            39 => Some(DSKey::ButtonRight), // Right arrow
-            // QWAS for shoulder and face buttons
-            81 => Some(DSKey::ButtonL), // Q
-            87 => Some(DSKey::ButtonR), // W
-            65 => Some(DSKey::ButtonY), // A
-            83 => Some(DSKey::ButtonX), // S
-            88 => Some(DSKey::ButtonA), // X
-            90 => Some(DSKey::ButtonB), // Z
-            // Action buttons
-            13 => Some(DSKey::ButtonStart),  // Return/Enter
-            32 => Some(DSKey::ButtonSelect), // Space
-            48 => Some(DSKey::Debugging),    // 0
-            // Tab for framelimiter toggle
-            9 => {
-                // TODO: Toggle framelimiter
-                None
+    }
+
+    /// Handle a keyboard scancode, resolving it through the remappable
+    /// binding table in [`Config`] and applying the result: DS buttons are
+    /// forwarded to the core, toggles flip the matching `Config` field,
+    /// `Screenshot` saves `screenshot.png`, and `Pause` is left for the caller
+    /// to act on
+    pub fn handle_key_press(&mut self, key_code: u32, pressed: bool) -> Option<InputAction> {
+        let action = self.input.lock().unwrap().handle_key_code(key_code, pressed)?;
+
+        match action {
+            InputAction::Key(key) => self.core.lock().unwrap().set_key(key, pressed),
+            InputAction::ToggleFramelimiter if pressed => {
+                let mut config = Config::lock();
+                config.enable_framelimiter = !config.enable_framelimiter;
             }
-            // O for frameskip toggle
-            79 => {
-                // TODO: Toggle frameskip
-                None
+            InputAction::ToggleFrameskip if pressed => {
+                let mut config = Config::lock();
+                config.frameskip = !config.frameskip;
             }
-            // P for manual pause
-            80 => {
-                // TODO: Manual pause
-                None
+            InputAction::ToggleFramelimiter | InputAction::ToggleFrameskip => {}
+            InputAction::Screenshot if pressed => {
+                if let Err(e) = self.save_screenshot("screenshot.png") {
+                    eprintln!("Failed to save screenshot: {}", e);
+                }
             }
-            _ => None,
+            InputAction::Pause | InputAction::Screenshot => {}
         }
+
+        Some(action)
     }
 
-    /// Handle touchscreen input
-    pub fn handle_touchscreen(&self, x: i32, y: i32) {
-        if y > SCANLINES as i32 {
-            let touch_x = x;
-            let touch_y = y - SCANLINES as i32;
-            if touch_y >= 0 && touch_y < SCANLINES as i32 {
-                println!("Touchscreen event: ({}, {})", touch_x, touch_y);
-                // TODO: Send touchscreen event to emulation thread
+    /// Poll the active gamepad and apply any resolved actions, the same way
+    /// `handle_key_press` does for the keyboard
+    pub fn poll_gamepad(&mut self) {
+        let actions = self.input.lock().unwrap().poll_gamepad();
+        for action in actions {
+            match action {
+                InputAction::ToggleFramelimiter => {
+                    let mut config = Config::lock();
+                    config.enable_framelimiter = !config.enable_framelimiter;
+                }
+                InputAction::ToggleFrameskip => {
+                    let mut config = Config::lock();
+                    config.frameskip = !config.frameskip;
+                }
+                InputAction::Key(_) | InputAction::Pause | InputAction::Screenshot => {}
             }
         }
     }
 
+    /// Set or clear a DS button via the owned `CorgiCore`, bypassing the
+    /// binding table (used by on-screen touch controls)
+    pub fn set_key(&mut self, key: DSKey, pressed: bool) {
+        self.input.lock().unwrap().set_key(key, pressed);
+        self.core.lock().unwrap().set_key(key, pressed);
+    }
+
+    /// Configure where the frontend places the upper/lower screens and at
+    /// what scale, so `handle_touch_down`/`handle_touch_drag` can translate
+    /// window-space coordinates correctly
+    pub fn set_screen_layout(&mut self, layout: ScreenLayout, scale: f32) {
+        self.touch_mapper = TouchMapper::new(layout, scale);
+    }
+
+    /// Enable a standard D-pad/face-button/shoulder overlay sized to a
+    /// `viewport_width` x `viewport_height` window, for touch-only frontends
+    pub fn enable_on_screen_controls(&mut self, viewport_width: i32, viewport_height: i32) {
+        self.on_screen_controls = Some(OnScreenControls::standard_layout(viewport_width, viewport_height));
+    }
+
+    pub fn disable_on_screen_controls(&mut self) {
+        self.on_screen_controls = None;
+    }
+
+    /// A touch/click landed at window-space `(x, y)`: if it falls on an
+    /// on-screen control, press the matching DS button; otherwise, if it
+    /// falls on the lower screen (per the configured layout/scale), forward
+    /// it to the TSC as a pen-down
+    pub fn handle_touch_down(&mut self, touch_id: u32, x: i32, y: i32) {
+        if let Some(controls) = &mut self.on_screen_controls {
+            if let Some(key) = controls.touch_down(touch_id, x, y) {
+                self.set_key(key, true);
+                return;
+            }
+        }
+
+        if let Some((lx, ly)) = self.touch_mapper.map(x, y) {
+            self.core.lock().unwrap().touch(lx, ly);
+        }
+    }
+
+    /// The touch identified by `touch_id` dragged to window-space `(x, y)`:
+    /// updates whichever on-screen button it's now over, or drags the TSC
+    /// pen position if it's on the lower screen
+    pub fn handle_touch_drag(&mut self, touch_id: u32, x: i32, y: i32) {
+        if let Some(controls) = &mut self.on_screen_controls {
+            let (released, pressed) = controls.touch_move(touch_id, x, y);
+            if let Some(key) = released {
+                self.set_key(key, false);
+            }
+            if let Some(key) = pressed {
+                self.set_key(key, true);
+            }
+            if released.is_some() || pressed.is_some() {
+                return;
+            }
+        }
+
+        if let Some((lx, ly)) = self.touch_mapper.map(x, y) {
+            self.core.lock().unwrap().touch(lx, ly);
+        } else {
+            self.core.lock().unwrap().release_touch();
+        }
+    }
+
+    /// The touch identified by `touch_id` was released: release whichever
+    /// on-screen button it held, and release the TSC pen either way (a drag
+    /// off-screen should still lift the pen)
+    pub fn handle_touch_up(&mut self, touch_id: u32) {
+        if let Some(controls) = &mut self.on_screen_controls {
+            if let Some(key) = controls.touch_up(touch_id) {
+                self.set_key(key, false);
+            }
+        }
+        self.core.lock().unwrap().release_touch();
+    }
+
     /// Load ROM file
     pub fn load_rom(&mut self, path: &str) -> Result<(), String> {
-        /// Check if firmware is loaded
-        // TODO: Verify firmware is loaded
-
-        /// Load the ROM file
-        println!("Loading ROM: {}", path);
+        self.core.lock().unwrap().load_rom(path)?;
         self.rom_file_name = path.to_string();
-
-        /// Start emulation
-        self.is_emulating = true;
         Ok(())
     }
 
-    /// Save screenshot to file
+    /// Save a screenshot: the upper and lower screens stacked into one PNG
     pub fn save_screenshot(&self, path: &str) -> Result<(), String> {
-        if let Ok(fb) = self.frame_buffer.lock() {
-            println!("Saving screenshot to: {}", path);
-            // TODO: Implement actual screenshot saving
-            Ok(())
-        } else {
-            Err("Failed to acquire frame buffer lock".to_string())
-        }
+        let (upper, lower) = self.current_frame()?;
+        crate::capture::save_png_stacked(&upper, &lower, PIXELS_PER_LINE, SCANLINES, path)
+    }
+
+    /// Save a screenshot as two separate PNGs, one per screen
+    pub fn save_screenshot_separate(&self, upper_path: &str, lower_path: &str) -> Result<(), String> {
+        let (upper, lower) = self.current_frame()?;
+        crate::capture::save_png_separate(&upper, &lower, PIXELS_PER_LINE, SCANLINES, upper_path, lower_path)
+    }
+
+    fn current_frame(&self) -> Result<(Vec<u32>, Vec<u32>), String> {
+        let core = self.core.lock().map_err(|_| "Failed to acquire core lock".to_string())?;
+        let mut upper = vec![0u32; PIXELS_PER_LINE * SCANLINES];
+        let mut lower = vec![0u32; PIXELS_PER_LINE * SCANLINES];
+        core.copy_framebuffers(&mut upper, &mut lower);
+        Ok((upper, lower))
+    }
+
+    /// Start recording frames from `draw_frame`, discarding anything
+    /// captured during a previous session
+    pub fn start_recording(&mut self, format: RecordingFormat) {
+        self.recorder.lock().unwrap().start(format);
+    }
+
+    /// Stop the current recording and flush it to `path`
+    pub fn stop_recording(&mut self, path: &str) -> Result<(), String> {
+        self.recorder.lock().unwrap().stop_and_save(path)
+    }
+
+    /// Whether a recording is currently in progress
+    pub fn is_recording(&self) -> bool {
+        self.recorder.lock().unwrap().is_recording()
     }
 
     /// Show preferences dialog
     pub fn show_preferences(&self) {
-        println!("Opening preferences dialog");
         // TODO: Implement preferences dialog
     }
 
     /// Show about dialog
     pub fn show_about(&self) {
-        println!("CorgiDS v0.1 - Created by PSISP");
         // TODO: Implement about dialog
     }
 }
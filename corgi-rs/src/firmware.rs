@@ -2,24 +2,69 @@
 /// Handles firmware data loading, CRC verification, and SPI data transfer
 use std::sync::{Arc, Mutex};
 
-/// Firmware commands
+/// Touchscreen calibration points stored in the firmware user-settings block:
+/// two reference pairs mapping an ADC reading to a screen pixel.
+#[derive(Debug, Clone, Copy)]
+pub struct TouchCalibration {
+    pub adc_x1: i32,
+    pub adc_y1: i32,
+    pub scr_x1: i32,
+    pub scr_y1: i32,
+    pub adc_x2: i32,
+    pub adc_y2: i32,
+    pub scr_x2: i32,
+    pub scr_y2: i32,
+}
+
+impl Default for TouchCalibration {
+    /// Matches the plain `*4096/256`/`*4096/192` linear mapping used before
+    /// real firmware calibration was parsed.
+    fn default() -> Self {
+        TouchCalibration {
+            adc_x1: 0,
+            adc_y1: 0,
+            scr_x1: 0,
+            scr_y1: 0,
+            adc_x2: 4095,
+            adc_y2: 4095,
+            scr_x2: 255,
+            scr_y2: 191,
+        }
+    }
+}
+
+/// Firmware commands, keyed by their real SPI flash opcode
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FirmwareCommand {
     /// No command
-    None = 0,
+    None = 0x00,
+    /// Write enable: sets the WEL bit in the status register
+    WriteEnable = 0x06,
+    /// Write disable: clears the WEL bit
+    WriteDisable = 0x04,
     /// Read status register
-    ReadStatusReg = 1,
+    ReadStatusReg = 0x05,
     /// Read data stream
-    ReadStream = 2,
+    ReadStream = 0x03,
+    /// Page write/program: latches a 3-byte address then streams data in
+    PageWrite = 0x0A,
+    /// Page program (alternate opcode, same semantics as PageWrite)
+    PageProgram = 0x02,
+    /// Page erase: latches a 3-byte address, erases the containing page to 0xFF
+    PageErase = 0xDB,
 }
 
 impl FirmwareCommand {
-    /// Convert numeric value to FirmwareCommand
+    /// Convert numeric value (the raw SPI opcode byte) to a FirmwareCommand
     pub fn from_value(val: u32) -> Self {
         match val {
-            0 => FirmwareCommand::None,
-            1 => FirmwareCommand::ReadStatusReg,
-            2 => FirmwareCommand::ReadStream,
+            0x06 => FirmwareCommand::WriteEnable,
+            0x04 => FirmwareCommand::WriteDisable,
+            0x05 => FirmwareCommand::ReadStatusReg,
+            0x03 => FirmwareCommand::ReadStream,
+            0x0A => FirmwareCommand::PageWrite,
+            0x02 => FirmwareCommand::PageProgram,
+            0xDB => FirmwareCommand::PageErase,
             _ => FirmwareCommand::None,
         }
     }
@@ -44,12 +89,43 @@ pub struct Firmware {
     address: u32,
     /// Total arguments for command
     total_args: i32,
+
+    /// Touchscreen calibration parsed from the user-settings block
+    calibration: TouchCalibration,
+
+    /// Byte offset of whichever user-settings copy is currently active
+    active_settings_offset: usize,
+    /// Whether the WiFi config block's CRC16 validated on load
+    wifi_config_valid: bool,
 }
 
 impl Firmware {
     /// Firmware size in bytes (256 KB)
     pub const SIZE: usize = 1024 * 256;
 
+    /// Status register bit 0: write-in-progress
+    const WIP_BIT: u8 = 1 << 0;
+    /// Status register bit 1: write-enable-latch
+    const WEL_BIT: u8 = 1 << 1;
+    /// Flash page size used by PageWrite/PageProgram/PageErase
+    const PAGE_SIZE: usize = 0x100;
+
+    /// WiFi config block: start offset, CRC-protected length, and where its CRC16 is stored
+    const WIFI_CONFIG_OFFSET: usize = 0x2C;
+    const WIFI_CONFIG_LENGTH: usize = 0x138;
+    const WIFI_CONFIG_CRC_OFFSET: usize = 0x2C + 0x138;
+
+    /// The two user-settings copies, each a self-contained, CRC-protected block
+    const USER_SETTINGS_SIZE: usize = 0x100;
+    const USER_SETTINGS_1_OFFSET: usize = Self::SIZE - 0x200;
+    const USER_SETTINGS_2_OFFSET: usize = Self::SIZE - 0x100;
+    /// Offset, within a user-settings block, of its 16-bit update counter
+    const SETTINGS_COUNTER_OFFSET: usize = 0x70;
+    /// Length of the CRC-protected span at the start of a user-settings block
+    const SETTINGS_CRC_LENGTH: usize = 0x72;
+    /// Offset, within a user-settings block, of its stored CRC16
+    const SETTINGS_CRC_OFFSET: usize = 0x72;
+
     /// Create new Firmware controller
     pub fn new() -> Self {
         Firmware {
@@ -60,17 +136,166 @@ impl Firmware {
             command_id: FirmwareCommand::None,
             address: 0,
             total_args: 0,
+            calibration: TouchCalibration::default(),
+            active_settings_offset: Self::USER_SETTINGS_1_OFFSET,
+            wifi_config_valid: false,
         }
     }
 
+    /// Offset of the active user-settings block's touchscreen calibration
+    /// data, relative to the start of that block (matches the layout real
+    /// DS firmware uses: ADC X1/Y1, screen X1/Y1, ADC X2/Y2, screen X2/Y2).
+    const TOUCH_CALIBRATION_OFFSET: usize = 0x58;
+
+    /// Parse the touchscreen calibration points out of the user-settings
+    /// block starting at `settings_offset` and store them for `TouchScreen`
+    /// to consume via [`Firmware::get_calibration`].
+    pub fn parse_user_settings(&mut self, settings_offset: usize) {
+        let base = settings_offset + Self::TOUCH_CALIBRATION_OFFSET;
+        if base + 12 > self.firmware.len() {
+            return;
+        }
+
+        let read_u16 = |f: &Firmware, off: usize| -> i32 {
+            (f.firmware[off] as i32) | ((f.firmware[off + 1] as i32) << 8)
+        };
+
+        self.calibration = TouchCalibration {
+            adc_x1: read_u16(self, base),
+            adc_y1: read_u16(self, base + 2),
+            scr_x1: self.firmware[base + 4] as i32,
+            scr_y1: self.firmware[base + 5] as i32,
+            adc_x2: read_u16(self, base + 6),
+            adc_y2: read_u16(self, base + 8),
+            scr_x2: self.firmware[base + 10] as i32,
+            scr_y2: self.firmware[base + 11] as i32,
+        };
+    }
+
+    /// Get the parsed touchscreen calibration
+    pub fn get_calibration(&self) -> TouchCalibration {
+        self.calibration
+    }
+
     /// Load firmware from file
-    /// Returns number of bytes loaded or error
-    pub fn load_firmware(&mut self, _file_name: &str) -> Result<usize, String> {
-        // In a real implementation, this would read from a file
-        // For now, initialize with default values
+    /// Returns number of bytes loaded or error. If the file doesn't exist, a
+    /// minimal valid firmware (default calibration, valid CRCs) is
+    /// synthesized so direct boot still works.
+    pub fn load_firmware(&mut self, file_name: &str) -> Result<usize, String> {
+        match std::fs::read(file_name) {
+            Ok(data) => {
+                if data.len() != Self::SIZE {
+                    return Err(format!(
+                        "Firmware file size mismatch: expected {} bytes, got {}",
+                        Self::SIZE,
+                        data.len()
+                    ));
+                }
+                self.firmware = data;
+            }
+            Err(_) => self.synthesize_default_firmware(),
+        }
+
         self.status_reg = 0x00;
         self.address = 0;
-        Ok(Self::SIZE)
+        self.command_id = FirmwareCommand::None;
+
+        self.wifi_config_valid = self.verify_crc(
+            0,
+            Self::WIFI_CONFIG_OFFSET,
+            Self::WIFI_CONFIG_LENGTH,
+            Self::WIFI_CONFIG_CRC_OFFSET,
+        );
+        self.select_active_user_settings();
+
+        Ok(self.firmware.len())
+    }
+
+    /// Build a from-scratch firmware image: erased flash, default calibration
+    /// baked into both user-settings copies, and valid CRC16s over each.
+    fn synthesize_default_firmware(&mut self) {
+        self.firmware = vec![0xFFu8; Self::SIZE];
+        self.write_default_user_settings(Self::USER_SETTINGS_1_OFFSET, 0);
+        self.write_default_user_settings(Self::USER_SETTINGS_2_OFFSET, 1);
+    }
+
+    /// Write a default user-settings block (calibration + update counter)
+    /// at `offset` and stamp its CRC16
+    fn write_default_user_settings(&mut self, offset: usize, counter: u16) {
+        for byte in self.firmware[offset..offset + Self::USER_SETTINGS_SIZE].iter_mut() {
+            *byte = 0;
+        }
+
+        let calib = TouchCalibration::default();
+        let cal_base = offset + Self::TOUCH_CALIBRATION_OFFSET;
+        self.firmware[cal_base] = calib.adc_x1 as u8;
+        self.firmware[cal_base + 1] = (calib.adc_x1 >> 8) as u8;
+        self.firmware[cal_base + 2] = calib.adc_y1 as u8;
+        self.firmware[cal_base + 3] = (calib.adc_y1 >> 8) as u8;
+        self.firmware[cal_base + 4] = calib.scr_x1 as u8;
+        self.firmware[cal_base + 5] = calib.scr_y1 as u8;
+        self.firmware[cal_base + 6] = calib.adc_x2 as u8;
+        self.firmware[cal_base + 7] = (calib.adc_x2 >> 8) as u8;
+        self.firmware[cal_base + 8] = calib.adc_y2 as u8;
+        self.firmware[cal_base + 9] = (calib.adc_y2 >> 8) as u8;
+        self.firmware[cal_base + 10] = calib.scr_x2 as u8;
+        self.firmware[cal_base + 11] = calib.scr_y2 as u8;
+
+        let counter_off = offset + Self::SETTINGS_COUNTER_OFFSET;
+        self.firmware[counter_off] = counter as u8;
+        self.firmware[counter_off + 1] = (counter >> 8) as u8;
+
+        let crc = Self::create_crc(&self.firmware, Self::SETTINGS_CRC_LENGTH, offset);
+        let crc_off = offset + Self::SETTINGS_CRC_OFFSET;
+        self.firmware[crc_off] = crc as u8;
+        self.firmware[crc_off + 1] = (crc >> 8) as u8;
+    }
+
+    /// Validate both user-settings copies by CRC16 and pick the active one:
+    /// prefer whichever copy's CRC is valid, and between two valid copies,
+    /// the one with the greater update counter (matching melonDS).
+    fn select_active_user_settings(&mut self) {
+        let valid1 = self.verify_crc(
+            0,
+            Self::USER_SETTINGS_1_OFFSET,
+            Self::SETTINGS_CRC_LENGTH,
+            Self::USER_SETTINGS_1_OFFSET + Self::SETTINGS_CRC_OFFSET,
+        );
+        let valid2 = self.verify_crc(
+            0,
+            Self::USER_SETTINGS_2_OFFSET,
+            Self::SETTINGS_CRC_LENGTH,
+            Self::USER_SETTINGS_2_OFFSET + Self::SETTINGS_CRC_OFFSET,
+        );
+
+        self.active_settings_offset = match (valid1, valid2) {
+            (true, true) => {
+                let counter_at = |offset: usize| -> u16 {
+                    let off = offset + Self::SETTINGS_COUNTER_OFFSET;
+                    (self.firmware[off] as u16) | ((self.firmware[off + 1] as u16) << 8)
+                };
+                if counter_at(Self::USER_SETTINGS_2_OFFSET) > counter_at(Self::USER_SETTINGS_1_OFFSET) {
+                    Self::USER_SETTINGS_2_OFFSET
+                } else {
+                    Self::USER_SETTINGS_1_OFFSET
+                }
+            }
+            (true, false) => Self::USER_SETTINGS_1_OFFSET,
+            (false, true) => Self::USER_SETTINGS_2_OFFSET,
+            (false, false) => Self::USER_SETTINGS_1_OFFSET,
+        };
+
+        self.parse_user_settings(self.active_settings_offset);
+    }
+
+    /// Byte offset of the user-settings copy currently in use
+    pub fn get_active_settings_offset(&self) -> usize {
+        self.active_settings_offset
+    }
+
+    /// Whether the WiFi config block's CRC16 validated on load
+    pub fn is_wifi_config_valid(&self) -> bool {
+        self.wifi_config_valid
     }
 
     /// Direct boot - initialize firmware for direct boot mode
@@ -88,9 +313,25 @@ impl Firmware {
         match self.command_id {
             FirmwareCommand::None => {
                 // Parse command byte
-                self.command_id = FirmwareCommand::from_value(input as u32);
-                self.total_args = 0;
-                self.address = 0;
+                let cmd = FirmwareCommand::from_value(input as u32);
+                match cmd {
+                    FirmwareCommand::WriteEnable => {
+                        self.status_reg |= Self::WEL_BIT;
+                    }
+                    FirmwareCommand::WriteDisable => {
+                        self.status_reg &= !Self::WEL_BIT;
+                    }
+                    other => {
+                        self.command_id = other;
+                        self.total_args = 0;
+                        self.address = 0;
+                    }
+                }
+                0x00
+            }
+            FirmwareCommand::WriteEnable | FirmwareCommand::WriteDisable => {
+                // These complete in a single byte and never become the active command
+                self.command_id = FirmwareCommand::None;
                 0x00
             }
             FirmwareCommand::ReadStatusReg => {
@@ -108,9 +349,59 @@ impl Firmware {
                     0x00
                 }
             }
+            FirmwareCommand::PageWrite | FirmwareCommand::PageProgram => {
+                if self.total_args < 3 {
+                    self.address = (self.address << 8) | (input as u32);
+                    self.total_args += 1;
+                    return 0x00;
+                }
+
+                if (self.status_reg & Self::WEL_BIT) != 0 {
+                    self.status_reg |= Self::WIP_BIT;
+
+                    let page_base = (self.address as usize) & !(Self::PAGE_SIZE - 1);
+                    let offset_in_page = (self.address as usize) % Self::PAGE_SIZE;
+                    let target = page_base + offset_in_page;
+                    if target < self.firmware.len() {
+                        self.firmware[target] = input;
+                    }
+
+                    // Address wraps within the page once the write reaches its end
+                    self.address = self.address.wrapping_add(1);
+                    if (self.address as usize) % Self::PAGE_SIZE == 0 {
+                        self.address -= Self::PAGE_SIZE as u32;
+                    }
+
+                    self.status_reg &= !Self::WIP_BIT;
+                }
+                0x00
+            }
+            FirmwareCommand::PageErase => {
+                if self.total_args < 3 {
+                    self.address = (self.address << 8) | (input as u32);
+                    self.total_args += 1;
+
+                    if self.total_args == 3 && (self.status_reg & Self::WEL_BIT) != 0 {
+                        self.status_reg |= Self::WIP_BIT;
+                        let page_base = (self.address as usize) & !(Self::PAGE_SIZE - 1);
+                        let page_end = (page_base + Self::PAGE_SIZE).min(self.firmware.len());
+                        for byte in self.firmware[page_base..page_end].iter_mut() {
+                            *byte = 0xFF;
+                        }
+                        self.status_reg &= !Self::WIP_BIT;
+                    }
+                }
+                0x00
+            }
         }
     }
 
+    /// Flush the in-memory firmware buffer back to disk so user settings
+    /// (language, name, birthday, calibration, boot counters) persist across runs
+    pub fn save_firmware(&self, path: &str) -> Result<(), String> {
+        std::fs::write(path, &self.firmware).map_err(|e| e.to_string())
+    }
+
     /// Deselect firmware (end SPI transfer)
     pub fn deselect(&mut self) {
         self.command_id = FirmwareCommand::None;
@@ -3,6 +3,7 @@ use crate::memconsts::{PIXELS_PER_LINE, SCANLINES};
 /// This module provides a Rust-side representation of the GPU_3D class
 /// from the original emulator. Many functions are stubbed to match
 /// the original API surface; detailed implementation can be filled in later.
+use crate::renderer3d::{Renderer3D, SoftwareRenderer};
 use std::sync::{Arc, Mutex};
 
 /// Display 3D control registe
@@ -90,6 +91,62 @@ impl Mtx {
     pub fn set(&mut self, other: &Mtx) {
         self.m = other.m;
     }
+
+    /// The 20.12 fixed-point identity matrix
+    pub fn identity() -> Self {
+        let mut m = [[0i32; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            row[i] = 1 << 12;
+        }
+        Mtx { m }
+    }
+
+    /// Build a matrix from a flat GXFIFO parameter list, given column-by-column
+    /// (GBATEK's order for `MTX_LOAD`/`MTX_MULT`). `cols`/`rows` let the 4x3 and
+    /// 3x3 variants supply fewer entries; any row/column left unspecified keeps
+    /// its identity value (the 4x3 commands imply a `(0,0,0,1)` last row).
+    fn from_params(params: &[u32], cols: usize, rows: usize) -> Mtx {
+        let mut out = Mtx::identity();
+        let mut i = 0;
+        for c in 0..cols {
+            for r in 0..rows {
+                out.m[c][r] = params[i] as i32;
+                i += 1;
+            }
+        }
+        out
+    }
+
+    /// Multiply two 20.12 fixed-point matrices as `self * other` (column-vector
+    /// convention: `other` is applied first). Column `c`, row `r` is stored at
+    /// `m[c][r]`; each element accumulates as a 64-bit product of the two
+    /// 20.12 operands before shifting back down by 12 bits.
+    pub fn mul(&self, other: &Mtx) -> Mtx {
+        let mut out = Mtx::default();
+        for c in 0..4 {
+            for r in 0..4 {
+                let mut acc: i64 = 0;
+                for k in 0..4 {
+                    acc += (self.m[k][r] as i64) * (other.m[c][k] as i64);
+                }
+                out.m[c][r] = (acc >> 12) as i32;
+            }
+        }
+        out
+    }
+
+    /// Transform a 20.12 fixed-point column vector `[x, y, z, w]` by this matrix
+    pub fn transform_vec4(&self, v: [i32; 4]) -> [i32; 4] {
+        let mut out = [0i32; 4];
+        for r in 0..4 {
+            let mut acc: i64 = 0;
+            for (c, item) in v.iter().enumerate() {
+                acc += (self.m[c][r] as i64) * (*item as i64);
+            }
+            out[r] = (acc >> 12) as i32;
+        }
+        out
+    }
 }
 
 /// Vertex structure used in geometry pipeline
@@ -145,11 +202,58 @@ impl Default for Polygon {
     }
 }
 
-/// Simple GX command (command byte + parameter)
-#[derive(Debug, Clone, Copy, Default)]
+/// A fully-assembled GX command: the command byte plus every parameter word
+/// collected for it, ready for `exec_command` to dispatch
+#[derive(Debug, Clone, Default)]
 pub struct GxCommand {
     pub command: u8,
-    pub param: u32,
+    pub params: Vec<u32>,
+}
+
+/// Parameter word count required by each GXFIFO command byte, per GBATEK's
+/// 3D engine command summary. Undefined/reserved opcodes take 0, matching
+/// real hardware's behavior of ignoring them.
+fn gx_command_param_count(command: u8) -> u8 {
+    match command {
+        0x10 => 1,  // MTX_MODE
+        0x11 => 0,  // MTX_PUSH
+        0x12 => 1,  // MTX_POP
+        0x13 => 1,  // MTX_STORE
+        0x14 => 1,  // MTX_RESTORE
+        0x15 => 0,  // MTX_IDENTITY
+        0x16 => 16, // MTX_LOAD_4x4
+        0x17 => 12, // MTX_LOAD_4x3
+        0x18 => 16, // MTX_MULT_4x4
+        0x19 => 12, // MTX_MULT_4x3
+        0x1A => 9,  // MTX_MULT_3x3
+        0x1B => 3,  // MTX_SCALE
+        0x1C => 3,  // MTX_TRANS
+        0x20 => 1,  // COLOR
+        0x21 => 1,  // NORMAL
+        0x22 => 1,  // TEXCOORD
+        0x23 => 2,  // VTX_16
+        0x24 => 1,  // VTX_10
+        0x25 => 1,  // VTX_XY
+        0x26 => 1,  // VTX_XZ
+        0x27 => 1,  // VTX_YZ
+        0x28 => 1,  // VTX_DIFF
+        0x29 => 1,  // POLYGON_ATTR
+        0x2A => 1,  // TEXIMAGE_PARAM
+        0x2B => 1,  // PLTT_BASE
+        0x30 => 1,  // DIF_AMB
+        0x31 => 1,  // SPE_EMI
+        0x32 => 1,  // LIGHT_VECTOR
+        0x33 => 1,  // LIGHT_COLOR
+        0x34 => 32, // SHININESS
+        0x40 => 1,  // BEGIN_VTXS
+        0x41 => 0,  // END_VTXS
+        0x50 => 1,  // SWAP_BUFFERS
+        0x60 => 1,  // VIEWPORT
+        0x70 => 3,  // BOX_TEST
+        0x71 => 2,  // POS_TEST
+        0x72 => 1,  // VEC_TEST
+        _ => 0,
+    }
 }
 
 /// 3D GPU core
@@ -175,16 +279,18 @@ pub struct Gpu3D {
     gx_fifo: std::collections::VecDeque<GxCommand>,
     gx_pipe: std::collections::VecDeque<GxCommand>,
 
+    /// Command bytes decoded from a packed 0x4000400 write that are still
+    /// waiting for their turn to start collecting parameters
+    pending_cmd_bytes: std::collections::VecDeque<u8>,
+
     cmd_params: [u32; 32],
     param_count: u8,
-    cmd_param_count: u8,
-    cmd_count: u8,
     total_params: u8,
     current_cmd: u32,
     current_poly_attr: PolygonAttr,
 
     current_color: u32,
-    current_vertex: [i16; 3],
+    current_vertex: [i32; 3],
     current_texcoords: [i16; 2],
 
     z_buffer: Vec<Vec<u32>>,
@@ -240,6 +346,10 @@ pub struct Gpu3D {
 
     mult_params: Mtx,
     mult_params_index: i32,
+
+    /// Rasterizer backend driving `render_scanline`; `SoftwareRenderer` by
+    /// default, swappable via `set_renderer` (e.g. for a `WgpuRenderer`)
+    renderer: Box<dyn Renderer3D>,
 }
 
 impl Gpu3D {
@@ -268,15 +378,14 @@ impl Gpu3D {
             flush_mode: 0,
             gx_fifo: std::collections::VecDeque::new(),
             gx_pipe: std::collections::VecDeque::new(),
+            pending_cmd_bytes: std::collections::VecDeque::new(),
             cmd_params: [0u32; 32],
             param_count: 0,
-            cmd_param_count: 0,
-            cmd_count: 0,
             total_params: 0,
             current_cmd: 0,
             current_poly_attr: PolygonAttr::default(),
             current_color: 0,
-            current_vertex: [0i16; 3],
+            current_vertex: [0i32; 3],
             current_texcoords: [0i16; 2],
             z_buffer: zbuf,
             trans_poly_ids: vec![0u8; PIXELS_PER_LINE as usize],
@@ -329,63 +438,166 @@ impl Gpu3D {
             vec_test_result: [0i16; 3],
             mult_params: Mtx::default(),
             mult_params_index: 0,
+            renderer: Box::new(SoftwareRenderer::new()),
         }
     }
 
+    /// Install a different rasterizer backend (e.g. a feature-gated
+    /// `WgpuRenderer`) in place of the default `SoftwareRenderer`
+    pub fn set_renderer(&mut self, renderer: Box<dyn Renderer3D>) {
+        self.renderer = renderer;
+    }
+
+    /// Hardware GXFIFO capacity (entries), and the level at which the
+    /// "less than half full" GXSTAT/IRQ condition becomes true
+    const GXFIFO_CAPACITY: usize = 256;
+    const GXFIFO_HALF: usize = 128;
+
     /// Power on GPU 3D unit
     pub fn power_on(&mut self) {
         self.cycles = 0;
         self.gx_fifo.clear();
         self.gx_pipe.clear();
+        self.pending_cmd_bytes.clear();
+        self.current_cmd = 0;
+        self.total_params = 0;
+        self.param_count = 0;
         // reset other state as needed
     }
 
-    /// Render a single scanline into the provided framebuffer
+    /// Copy one scanline of the renderer's already-rasterized frame into the
+    /// 2D engine's framebuffer, at whichever line `VCOUNT` currently reads
     pub fn render_scanline(
         &mut self,
-        _framebuffer: &mut [u32],
+        framebuffer: &mut [u32],
         _bg_priorities: &[u8],
         _bg0_priority: u8,
     ) {
-        // Stubbed: detailed rasterization not implemented
+        let vcount = if let Some(gpu) = &self.gpu {
+            gpu.lock().unwrap().get_VCOUNT() as usize
+        } else {
+            0
+        };
+
+        let width = PIXELS_PER_LINE as usize;
+        let line_start = vcount * width;
+        let rendered = self.renderer.framebuffer();
+        if line_start + width > rendered.len() || line_start + width > framebuffer.len() {
+            return;
+        }
+        framebuffer[line_start..line_start + width]
+            .copy_from_slice(&rendered[line_start..line_start + width]);
     }
 
     /// Run the 3D engine for given cycles
     pub fn run(&mut self, _cycles_to_run: u64) {
-        // Process commands from GXFIFO
+        // Drain fully-assembled commands into the geometry engine
         while let Some(cmd) = self.gx_fifo.pop_front() {
-            // execute or queue
             self.exec_command(cmd);
         }
+        self.gxstat.geo_busy = false;
+        self.check_fifo_irq();
     }
 
-    /// Called at end of frame
+    /// Called at end of frame: swap the geometry buffers into the rendering
+    /// buffers if SWAP_BUFFERS was issued, then rasterize the new frame
     pub fn end_of_frame(&mut self) {
-        // swap buffers if requested
         if self.swap_buffers {
             self.swap_buffers = false;
+
+            std::mem::swap(&mut self.geo_vert, &mut self.rend_vert);
+            std::mem::swap(&mut self.geo_poly, &mut self.rend_poly);
+            std::mem::swap(&mut self.geo_vert_count, &mut self.rend_vert_count);
+            std::mem::swap(&mut self.geo_poly_count, &mut self.rend_poly_count);
+            self.geo_vert_count = 0;
+            self.geo_poly_count = 0;
+
+            self.present_frame();
         }
     }
 
-    /// Check FIFO DMA (stub)
-    pub fn check_fifo_dma(&mut self) {}
+    /// Feed every rendering-buffer polygon through the installed
+    /// `Renderer3D` backend to produce this frame's 256x192 pixels
+    fn present_frame(&mut self) {
+        self.renderer.begin_frame(self.clear_color, self.clear_depth);
+        for i in 0..self.rend_poly_count as usize {
+            let poly = self.rend_poly[i].clone();
+            let start = poly.vert_index as usize;
+            let end = start + poly.vertices as usize;
+            if end > self.rend_vert.len() {
+                continue;
+            }
+            self.renderer.submit_polygon(&poly, &self.rend_vert[start..end], &self.disp3dcnt);
+        }
+        self.renderer.finish_frame();
+    }
 
-    /// Check FIFO IRQ (stub)
-    pub fn check_fifo_irq(&mut self) {}
+    /// Request a GXFIFO DMA transfer when the FIFO has room, mirroring how
+    /// real hardware lets a DMA channel configured for the "GXFIFO" start
+    /// timing keep feeding the packed port automatically
+    pub fn check_fifo_dma(&mut self) {
+        if self.gx_fifo.len() < Self::GXFIFO_HALF {
+            self.request_fifo_dma();
+        }
+    }
 
-    /// Write a 32-bit word into GXFIFO (incoming command)
-    pub fn write_gxfifo(&mut self, word: u32) {
-        // rudimentary command push: low byte = command, param = word
-        let cmd = GxCommand {
-            command: (word & 0xFF) as u8,
-            param: word,
+    /// Raise the GeometryFifo interrupt if GXSTAT's `gx_fifo_irq_stat`
+    /// condition (never / less-than-half-full / empty) currently holds
+    pub fn check_fifo_irq(&mut self) {
+        let should_request = match self.gxstat.gx_fifo_irq_stat {
+            1 => self.gx_fifo.len() < Self::GXFIFO_HALF,
+            2 => self.gx_fifo.is_empty(),
+            _ => false,
         };
-        self.gx_fifo.push_back(cmd);
+        if should_request {
+            if let Some(emu) = &self.emulator {
+                emu.lock()
+                    .unwrap()
+                    .request_interrupt(crate::interrupts::Interrupt::GeometryFifo);
+            }
+        }
     }
 
-    /// Direct FIFO write (addressed)
-    pub fn write_fifo_direct(&mut self, _address: u32, word: u32) {
-        self.write_gxfifo(word);
+    /// Write a 32-bit word through the packed GXFIFO port (0x4000400). A
+    /// word packs up to four command bytes when no command is already
+    /// mid-parameter-collection; otherwise it supplies the next parameter
+    /// word for the command currently being assembled.
+    pub fn write_gxfifo(&mut self, word: u32) {
+        if self.total_params == 0 {
+            for shift in [0, 8, 16, 24] {
+                let byte = ((word >> shift) & 0xFF) as u8;
+                self.pending_cmd_bytes.push_back(byte);
+            }
+            self.drain_pending_commands();
+        } else {
+            self.supply_param(word);
+        }
+    }
+
+    /// Write through a direct/addressed GXFIFO port (0x4000440+): the
+    /// command is implied by `address`, and each write after the first
+    /// supplies the next parameter word for that command.
+    pub fn write_fifo_direct(&mut self, address: u32, word: u32) {
+        let offset = address.wrapping_sub(0x0400_0440) / 4;
+        let cmd = (0x10u32.wrapping_add(offset) & 0xFF) as u8;
+
+        if self.total_params != 0 && self.current_cmd == cmd as u32 {
+            self.supply_param(word);
+            return;
+        }
+
+        // Starting a new direct-port command: any stale in-progress command
+        // is discarded, since a fresh address write always begins its own
+        // independent parameter stream on hardware.
+        self.current_cmd = cmd as u32;
+        self.total_params = gx_command_param_count(cmd);
+        self.param_count = 0;
+
+        if self.total_params == 0 {
+            self.dispatch_current(&[]);
+        } else {
+            self.supply_param(word);
+        }
     }
 
     /// Get raw DISP3DCNT register as 16-bit value
@@ -394,9 +606,23 @@ impl Gpu3D {
         0
     }
 
-    /// Get GXSTAT register value
+    /// Get GXSTAT register value. Bit layout approximates GBATEK: bit 0
+    /// box-test result, bit 1 box/pos/vec test busy, bit 13 matrix stack
+    /// busy, bit 15 matrix stack overflow, bits 16-24 GXFIFO entry count,
+    /// bit 25 GXFIFO less-than-half-full, bit 26 GXFIFO empty, bit 27
+    /// geometry engine busy, bits 30-31 GXFIFO IRQ condition.
     pub fn get_gxstat(&self) -> u32 {
-        0
+        let mut reg = 0u32;
+        reg |= self.gxstat.boxtest_result as u32;
+        reg |= (self.gxstat.box_pos_vec_busy as u32) << 1;
+        reg |= (self.gxstat.mtx_stack_busy as u32) << 13;
+        reg |= (self.gxstat.mtx_overflow as u32) << 15;
+        reg |= ((self.gx_fifo.len() as u32) & 0x1FF) << 16;
+        reg |= ((self.gx_fifo.len() < Self::GXFIFO_HALF) as u32) << 25;
+        reg |= (self.gx_fifo.is_empty() as u32) << 26;
+        reg |= (self.gxstat.geo_busy as u32) << 27;
+        reg |= ((self.gxstat.gx_fifo_irq_stat as u32) & 0x3) << 30;
+        reg
     }
 
     /// Get vertex count
@@ -431,34 +657,294 @@ impl Gpu3D {
         self.clear_depth = word;
     }
 
-    /// Matrix mode and stack operations (stubs)
-    pub fn set_mtx_mode(&mut self, _word: u32) {}
-    pub fn mtx_push(&mut self) {}
-    pub fn mtx_pop(&mut self, _word: u32) {}
-    pub fn mtx_identity(&mut self) {}
-    pub fn mtx_mult_4x4(&mut self, _word: u32) {}
-    pub fn mtx_mult_4x3(&mut self, _word: u32) {}
-    pub fn mtx_mult_3x3(&mut self, _word: u32) {}
-    pub fn mtx_trans(&mut self, _word: u32) {}
+    /// Select which matrix MTX_* commands operate on: 0=Projection,
+    /// 1=Position, 2=Position+Vector (used for lighting normals), 3=Texture
+    pub fn set_mtx_mode(&mut self, word: u32) {
+        self.mtx_mode = (word & 0x3) as u8;
+    }
+
+    /// Push the current matrix (and vector matrix, in Position+Vector mode)
+    /// onto its stack, flagging a stack overflow instead of panicking
+    pub fn mtx_push(&mut self) {
+        match self.mtx_mode {
+            0 => self.projection_stack = self.projection_mtx.clone(),
+            3 => self.texture_stack = self.texture_mtx.clone(),
+            _ => {
+                let sp = self.modelview_sp as usize;
+                if sp >= self.modelview_stack.len() {
+                    self.gxstat.mtx_overflow = true;
+                    return;
+                }
+                self.modelview_stack[sp] = self.modelview_mtx.clone();
+                self.vector_stack[sp] = self.vector_mtx.clone();
+                self.modelview_sp = self.modelview_sp.saturating_add(1);
+            }
+        }
+        self.gxstat.mtx_stack_busy = true;
+    }
 
-    /// Color and normal commands
-    pub fn color(&mut self, _word: u32) {}
+    /// Pop `word`'s signed 6-bit entry count off the current stack and
+    /// restore the matrix (and vector matrix) from the resulting position
+    pub fn mtx_pop(&mut self, word: u32) {
+        if self.mtx_mode == 0 || self.mtx_mode == 3 {
+            // The projection/texture stacks are a single entry deep and have
+            // no pop-count; MTX_POP only applies to the position stack.
+            return;
+        }
+        let raw = (word & 0x3F) as i32;
+        let offset = if raw >= 32 { raw - 64 } else { raw };
+        let mut sp = self.modelview_sp as i32 - offset;
+        if sp < 0 || sp as usize >= self.modelview_stack.len() {
+            self.gxstat.mtx_overflow = true;
+            sp = sp.clamp(0, self.modelview_stack.len() as i32 - 1);
+        }
+        self.modelview_sp = sp as u8;
+        self.modelview_mtx = self.modelview_stack[sp as usize].clone();
+        self.vector_mtx = self.vector_stack[sp as usize].clone();
+        self.clip_dirty = true;
+    }
+
+    /// Store the current matrix into stack slot `word & 0x1F` without moving
+    /// the stack pointer (the projection/texture stacks ignore the address)
+    pub fn mtx_store(&mut self, word: u32) {
+        match self.mtx_mode {
+            0 => self.projection_stack = self.projection_mtx.clone(),
+            3 => self.texture_stack = self.texture_mtx.clone(),
+            _ => {
+                let addr = (word & 0x1F) as usize;
+                if addr >= self.modelview_stack.len() {
+                    self.gxstat.mtx_overflow = true;
+                    return;
+                }
+                self.modelview_stack[addr] = self.modelview_mtx.clone();
+                self.vector_stack[addr] = self.vector_mtx.clone();
+            }
+        }
+    }
+
+    /// Restore the current matrix from stack slot `word & 0x1F`
+    pub fn mtx_restore(&mut self, word: u32) {
+        match self.mtx_mode {
+            0 => self.projection_mtx = self.projection_stack.clone(),
+            3 => self.texture_mtx = self.texture_stack.clone(),
+            _ => {
+                let addr = (word & 0x1F) as usize;
+                if addr >= self.modelview_stack.len() {
+                    self.gxstat.mtx_overflow = true;
+                    return;
+                }
+                self.modelview_mtx = self.modelview_stack[addr].clone();
+                self.vector_mtx = self.vector_stack[addr].clone();
+            }
+        }
+        self.clip_dirty = true;
+    }
+
+    /// Reset the matrix selected by `mtx_mode` to the 20.12 identity
+    pub fn mtx_identity(&mut self) {
+        self.set_current_matrix(Mtx::identity());
+    }
+
+    pub fn mtx_load_4x4(&mut self, params: &[u32]) {
+        self.set_current_matrix(Mtx::from_params(params, 4, 4));
+    }
+    pub fn mtx_load_4x3(&mut self, params: &[u32]) {
+        self.set_current_matrix(Mtx::from_params(params, 4, 3));
+    }
+    pub fn mtx_mult_4x4(&mut self, params: &[u32]) {
+        self.mult_current_matrix(&Mtx::from_params(params, 4, 4));
+    }
+    pub fn mtx_mult_4x3(&mut self, params: &[u32]) {
+        self.mult_current_matrix(&Mtx::from_params(params, 4, 3));
+    }
+    pub fn mtx_mult_3x3(&mut self, params: &[u32]) {
+        self.mult_current_matrix(&Mtx::from_params(params, 3, 3));
+    }
+
+    /// Scale the current matrix by the three 20.12 factors in `params`
+    pub fn mtx_scale(&mut self, params: &[u32]) {
+        let mut m = Mtx::identity();
+        m.m[0][0] = params[0] as i32;
+        m.m[1][1] = params[1] as i32;
+        m.m[2][2] = params[2] as i32;
+        self.mult_current_matrix(&m);
+    }
+
+    /// Translate the current matrix by the three 20.12 offsets in `params`
+    pub fn mtx_trans(&mut self, params: &[u32]) {
+        let mut m = Mtx::identity();
+        m.m[3][0] = params[0] as i32;
+        m.m[3][1] = params[1] as i32;
+        m.m[3][2] = params[2] as i32;
+        self.mult_current_matrix(&m);
+    }
+
+    /// Replace the matrix(es) selected by `mtx_mode`, marking the clip matrix
+    /// dirty so it's rebuilt lazily on the next vertex
+    fn set_current_matrix(&mut self, m: Mtx) {
+        match self.mtx_mode {
+            0 => self.projection_mtx = m,
+            1 => self.modelview_mtx = m,
+            2 => {
+                self.modelview_mtx = m.clone();
+                self.vector_mtx = m;
+            }
+            3 => self.texture_mtx = m,
+            _ => {}
+        }
+        self.clip_dirty = true;
+    }
+
+    /// Multiply the matrix(es) selected by `mtx_mode` by `m` on the right
+    fn mult_current_matrix(&mut self, m: &Mtx) {
+        match self.mtx_mode {
+            0 => self.projection_mtx = self.projection_mtx.mul(m),
+            1 => self.modelview_mtx = self.modelview_mtx.mul(m),
+            2 => {
+                self.modelview_mtx = self.modelview_mtx.mul(m);
+                self.vector_mtx = self.vector_mtx.mul(m);
+            }
+            3 => self.texture_mtx = self.texture_mtx.mul(m),
+            _ => {}
+        }
+        self.clip_dirty = true;
+    }
+
+    /// Color, normal and vertex commands
+    pub fn color(&mut self, word: u32) {
+        self.current_color = word & 0x7FFF;
+    }
     pub fn normal(&mut self) {}
+    pub fn texcoord(&mut self, word: u32) {
+        self.current_texcoords[0] = (word & 0xFFFF) as i16;
+        self.current_texcoords[1] = ((word >> 16) & 0xFFFF) as i16;
+    }
+
+    /// VTX_16: two 1.3.12 fixed-point values per parameter word (x|y, then z)
+    pub fn vtx_16(&mut self, params: &[u32]) {
+        self.current_vertex[0] = (params[0] & 0xFFFF) as i16 as i32;
+        self.current_vertex[1] = ((params[0] >> 16) & 0xFFFF) as i16 as i32;
+        self.current_vertex[2] = (params[1] & 0xFFFF) as i16 as i32;
+        self.add_vertex();
+    }
 
-    /// Polygon / texture setup
-    pub fn set_polygon_attr(&mut self, _word: u32) {}
-    pub fn set_teximage_param(&mut self, _word: u32) {}
+    /// VTX_10: three 1.5.4 fixed-point values packed 10 bits apiece, widened
+    /// to the matrices' 1.19.12 format by shifting the 4 fractional bits up to 12
+    pub fn vtx_10(&mut self, word: u32) {
+        let unpack = |raw: u32| -> i32 {
+            let v = (raw & 0x3FF) as i32;
+            let signed = if v >= 0x200 { v - 0x400 } else { v };
+            signed << 8
+        };
+        self.current_vertex[0] = unpack(word);
+        self.current_vertex[1] = unpack(word >> 10);
+        self.current_vertex[2] = unpack(word >> 20);
+        self.add_vertex();
+    }
+
+    pub fn vtx_xy(&mut self, word: u32) {
+        self.current_vertex[0] = (word & 0xFFFF) as i16 as i32;
+        self.current_vertex[1] = ((word >> 16) & 0xFFFF) as i16 as i32;
+        self.add_vertex();
+    }
+    pub fn vtx_xz(&mut self, word: u32) {
+        self.current_vertex[0] = (word & 0xFFFF) as i16 as i32;
+        self.current_vertex[2] = ((word >> 16) & 0xFFFF) as i16 as i32;
+        self.add_vertex();
+    }
+    pub fn vtx_yz(&mut self, word: u32) {
+        self.current_vertex[1] = (word & 0xFFFF) as i16 as i32;
+        self.current_vertex[2] = ((word >> 16) & 0xFFFF) as i16 as i32;
+        self.add_vertex();
+    }
+
+    /// VTX_DIFF: three 10-bit signed offsets (same 1.5.4 packing as VTX_10),
+    /// added to the previous vertex rather than replacing it
+    pub fn vtx_diff(&mut self, word: u32) {
+        let unpack = |raw: u32| -> i32 {
+            let v = (raw & 0x3FF) as i32;
+            let signed = if v >= 0x200 { v - 0x400 } else { v };
+            signed << 8
+        };
+        self.current_vertex[0] += unpack(word);
+        self.current_vertex[1] += unpack(word >> 10);
+        self.current_vertex[2] += unpack(word >> 20);
+        self.add_vertex();
+    }
+
+    pub fn end_vtxs(&mut self) {}
+
+    /// Polygon / texture / material setup
+    pub fn set_polygon_attr(&mut self, word: u32) {
+        self.current_poly_attr = PolygonAttr {
+            light_enable: (word & 0xF) as i32,
+            polygon_mode: ((word >> 4) & 0x3) as i32,
+            render_back: (word & (1 << 6)) != 0,
+            render_front: (word & (1 << 7)) != 0,
+            set_new_trans_depth: (word & (1 << 11)) != 0,
+            render_1dot: (word & (1 << 12)) != 0,
+            render_far_intersect: (word & (1 << 13)) != 0,
+            depth_test_equal: (word & (1 << 14)) != 0,
+            fog_enable: (word & (1 << 15)) != 0,
+            alpha: ((word >> 16) & 0x1F) as i32,
+            id: ((word >> 24) & 0x3F) as i32,
+        };
+    }
+    pub fn set_teximage_param(&mut self, word: u32) {
+        self.teximage_param = TexImageParam {
+            vram_offset: (word & 0xFFFF) as i32,
+            repeat_s: (word & (1 << 16)) != 0,
+            repeat_t: (word & (1 << 17)) != 0,
+            flip_s: (word & (1 << 18)) != 0,
+            flip_t: (word & (1 << 19)) != 0,
+            s_size: ((word >> 20) & 0x7) as i32,
+            t_size: ((word >> 23) & 0x7) as i32,
+            format: ((word >> 26) & 0x7) as i32,
+            color0_transparent: (word & (1 << 29)) != 0,
+            transformation_mode: ((word >> 30) & 0x3) as i32,
+        };
+    }
     pub fn set_toon_table(&mut self, _address: u32, _color: u16) {}
+    pub fn set_pltt_base(&mut self, word: u32) {
+        self.pltt_base = word;
+    }
+    pub fn dif_amb(&mut self, _word: u32) {}
+    pub fn spe_emi(&mut self, _word: u32) {}
+    pub fn light_vector(&mut self, _word: u32) {}
+    pub fn light_color(&mut self, _word: u32) {}
+    pub fn shininess(&mut self, _params: &[u32]) {}
 
-    /// Begin vertices, swap buffers, viewport, tests
-    pub fn begin_vtxs(&mut self, _word: u32) {}
+    /// Start a new polygon strip/fan: `word & 0x3` selects separate
+    /// triangles/quads or triangle/quad strips
+    pub fn begin_vtxs(&mut self, word: u32) {
+        self.polygon_type = word & 0x3;
+        self.vertex_list_count = 0;
+        self.consecutive_polygons = 0;
+    }
     pub fn swap_buffers(&mut self, _word: u32) {
         self.swap_buffers = true;
     }
-    pub fn viewport_cmd(&mut self, _word: u32) {}
-    pub fn box_test(&mut self) {}
-    pub fn vec_test(&mut self) {}
-    pub fn set_gxstat(&mut self, _word: u32) {}
+    pub fn viewport_cmd(&mut self, word: u32) {
+        self.viewport = Viewport {
+            x1: (word & 0xFF) as u8,
+            y1: ((word >> 8) & 0xFF) as u8,
+            x2: ((word >> 16) & 0xFF) as u8,
+            y2: ((word >> 24) & 0xFF) as u8,
+        };
+    }
+    pub fn box_test(&mut self, _params: &[u32]) {}
+    pub fn pos_test(&mut self, _params: &[u32]) {}
+    pub fn vec_test(&mut self, _word: u32) {}
+
+    /// Write to GXSTAT: bit 15 acknowledges a matrix stack overflow, bits
+    /// 30-31 select the GXFIFO IRQ condition (never/less-half/empty)
+    pub fn set_gxstat(&mut self, word: u32) {
+        self.gxstat.gx_fifo_irq_stat = ((word >> 30) & 0x3) as i32;
+        if (word & (1 << 15)) != 0 {
+            self.gxstat.mtx_overflow = false;
+        }
+        self.check_fifo_irq();
+    }
 
     // Internal helpers
     fn read_command(&mut self) -> Option<GxCommand> {
@@ -469,54 +955,347 @@ impl Gpu3D {
         self.gx_pipe.push_back(cmd);
     }
 
+    /// Pop queued command bytes (decoded from a packed GXFIFO word) and
+    /// start each one in turn: zero-parameter commands dispatch immediately
+    /// and the loop continues, while one needing parameters stops the drain
+    /// until enough parameter words have arrived via `supply_param`.
+    fn drain_pending_commands(&mut self) {
+        while self.total_params == 0 {
+            let Some(byte) = self.pending_cmd_bytes.pop_front() else {
+                break;
+            };
+            if byte == 0 {
+                // Padding byte in a packed word that held fewer than 4
+                // real commands; not a valid GXFIFO opcode.
+                continue;
+            }
+
+            self.current_cmd = byte as u32;
+            self.total_params = gx_command_param_count(byte);
+            self.param_count = 0;
+
+            if self.total_params == 0 {
+                self.dispatch_current(&[]);
+            }
+        }
+    }
+
+    /// Feed the next parameter word to the command currently being
+    /// assembled; dispatches it once every parameter has arrived
+    fn supply_param(&mut self, word: u32) {
+        self.cmd_params[self.param_count as usize] = word;
+        self.param_count += 1;
+
+        if self.param_count >= self.total_params {
+            let count = self.total_params as usize;
+            let params: Vec<u32> = self.cmd_params[..count].to_vec();
+            self.dispatch_current(&params);
+            self.drain_pending_commands();
+        }
+    }
+
+    /// Push the fully-assembled current command onto the hardware GXFIFO
+    /// and reset the per-command assembly state
+    fn dispatch_current(&mut self, params: &[u32]) {
+        let entry = GxCommand {
+            command: self.current_cmd as u8,
+            params: params.to_vec(),
+        };
+        self.push_fifo_entry(entry);
+        self.current_cmd = 0;
+        self.total_params = 0;
+        self.param_count = 0;
+    }
+
+    /// Push a fully-assembled command onto the 256-entry hardware GXFIFO,
+    /// updating the busy flag and re-checking the FIFO IRQ condition
+    fn push_fifo_entry(&mut self, entry: GxCommand) {
+        if self.gx_fifo.len() >= Self::GXFIFO_CAPACITY {
+            // Real hardware stalls the CPU until space frees up; lacking a
+            // bus-stall model, drop the oldest queued entry instead of
+            // growing the FIFO unbounded.
+            self.gx_fifo.pop_front();
+        }
+        self.gx_fifo.push_back(entry);
+        self.gxstat.geo_busy = true;
+        self.check_fifo_irq();
+    }
+
     fn exec_command(&mut self, cmd: GxCommand) {
-        // Very small dispatcher based on command byte
+        let p = cmd.params.as_slice();
         match cmd.command {
-            // 0x00 - example: clear buffers
-            0x00 => {
-                // handle clear
-            }
+            0x10 => self.set_mtx_mode(p[0]),
+            0x11 => self.mtx_push(),
+            0x12 => self.mtx_pop(p[0]),
+            0x13 => self.mtx_store(p[0]),
+            0x14 => self.mtx_restore(p[0]),
+            0x15 => self.mtx_identity(),
+            0x16 => self.mtx_load_4x4(p),
+            0x17 => self.mtx_load_4x3(p),
+            0x18 => self.mtx_mult_4x4(p),
+            0x19 => self.mtx_mult_4x3(p),
+            0x1A => self.mtx_mult_3x3(p),
+            0x1B => self.mtx_scale(p),
+            0x1C => self.mtx_trans(p),
+            0x20 => self.color(p[0]),
+            0x21 => self.normal(),
+            0x22 => self.texcoord(p[0]),
+            0x23 => self.vtx_16(p),
+            0x24 => self.vtx_10(p[0]),
+            0x25 => self.vtx_xy(p[0]),
+            0x26 => self.vtx_xz(p[0]),
+            0x27 => self.vtx_yz(p[0]),
+            0x28 => self.vtx_diff(p[0]),
+            0x29 => self.set_polygon_attr(p[0]),
+            0x2A => self.set_teximage_param(p[0]),
+            0x2B => self.set_pltt_base(p[0]),
+            0x30 => self.dif_amb(p[0]),
+            0x31 => self.spe_emi(p[0]),
+            0x32 => self.light_vector(p[0]),
+            0x33 => self.light_color(p[0]),
+            0x34 => self.shininess(p),
+            0x40 => self.begin_vtxs(p[0]),
+            0x41 => self.end_vtxs(),
+            0x50 => self.swap_buffers(p[0]),
+            0x60 => self.viewport_cmd(p[0]),
+            0x70 => self.box_test(p),
+            0x71 => self.pos_test(p),
+            0x72 => self.vec_test(p[0]),
             _ => {
-                // unimplemented
+                // Unrecognized/reserved command byte: ignored, matching
+                // real hardware's behavior for undefined GXFIFO opcodes.
             }
         }
     }
 
-    fn add_mult_param(&mut self, _word: u32) {}
-    fn mtx_mult(&mut self, _update_vector: bool) {}
-    fn update_clip_mtx(&mut self) {}
+    /// Recompute `clip_mtx = projection_mtx * modelview_mtx` if either has
+    /// changed since the last vertex, matching the hardware's lazy rebuild
+    fn update_clip_mtx(&mut self) {
+        if !self.clip_dirty {
+            return;
+        }
+        self.clip_mtx = self.projection_mtx.mul(&self.modelview_mtx);
+        self.clip_dirty = false;
+    }
 
-    fn clip(
-        &mut self,
-        _v_list: &mut [Vertex],
-        _v_len: i32,
-        _clip_start: i32,
-        _add_attributes: bool,
-    ) -> i32 {
-        0
+    /// Signed distance of a clip-space vertex from one of the six view
+    /// frustum planes (`w - x`, `w + x`, `w - y`, `w + y`, `w - z`, `w + z`);
+    /// non-negative means the vertex is on the inside of that plane
+    fn plane_distance(plane: i32, v: &Vertex) -> i64 {
+        let w = v.coords[3] as i64;
+        let comp = match plane {
+            0 => v.coords[0] as i64,
+            1 => -(v.coords[0] as i64),
+            2 => v.coords[1] as i64,
+            3 => -(v.coords[1] as i64),
+            4 => v.coords[2] as i64,
+            _ => -(v.coords[2] as i64),
+        };
+        w - comp
     }
-    fn clip_plane(
-        &mut self,
-        _plane: i32,
-        _v_list: &mut [Vertex],
-        _v_len: i32,
-        _clip_start: i32,
-        _add_attributes: bool,
-    ) -> i32 {
-        0
+
+    /// Sutherland-Hodgman clip of a vertex ring against all six frustum
+    /// planes in turn, returning the clipped ring (empty if fully outside)
+    fn clip(&mut self, verts: &[Vertex]) -> Vec<Vertex> {
+        let mut ring = verts.to_vec();
+        for plane in 0..6 {
+            if ring.is_empty() {
+                break;
+            }
+            ring = self.clip_plane(plane, &ring);
+        }
+        ring
     }
-    fn clip_vertex(
-        &mut self,
-        _plane: i32,
-        _v_list: &mut Vertex,
-        _v_out: &mut Vertex,
-        _v_in: &mut Vertex,
-        _side: i32,
-        _add_attributes: bool,
-    ) {
+
+    /// Clip a vertex ring against a single frustum plane: walk consecutive
+    /// edges, keeping inside vertices and emitting an interpolated vertex
+    /// wherever an edge crosses the plane
+    fn clip_plane(&mut self, plane: i32, verts: &[Vertex]) -> Vec<Vertex> {
+        let len = verts.len();
+        let mut out = Vec::with_capacity(len + 1);
+        for i in 0..len {
+            let cur = &verts[i];
+            let prev = &verts[(i + len - 1) % len];
+            let d_cur = Self::plane_distance(plane, cur);
+            let d_prev = Self::plane_distance(plane, prev);
+
+            if d_cur >= 0 {
+                if d_prev < 0 {
+                    out.push(Self::clip_vertex(prev, cur, d_prev, d_cur));
+                }
+                out.push(cur.clone());
+            } else if d_prev >= 0 {
+                out.push(Self::clip_vertex(prev, cur, d_prev, d_cur));
+            }
+        }
+        out
+    }
+
+    /// Interpolate the vertex where an edge crosses a clip plane, blending
+    /// position, color and texcoords by `t = d_in / (d_in - d_out)`
+    fn clip_vertex(v_in: &Vertex, v_out: &Vertex, d_in: i64, d_out: i64) -> Vertex {
+        let denom = d_in - d_out;
+        let lerp = |a: i32, b: i32| -> i32 {
+            if denom == 0 {
+                return a;
+            }
+            (a as i64 + ((b as i64 - a as i64) * d_in) / denom) as i32
+        };
+
+        let mut coords = [0i32; 4];
+        for k in 0..4 {
+            coords[k] = lerp(v_in.coords[k], v_out.coords[k]);
+        }
+        let mut colors = [0i32; 3];
+        for k in 0..3 {
+            colors[k] = lerp(v_in.colors[k], v_out.colors[k]);
+        }
+        let mut texcoords = [0i32; 2];
+        for k in 0..2 {
+            texcoords[k] = lerp(v_in.texcoords[k], v_out.texcoords[k]);
+        }
+
+        Vertex {
+            coords,
+            colors,
+            final_colors: [0; 3],
+            clipped: true,
+            texcoords,
+        }
+    }
+
+    /// Commit the current vertex (transformed through the clip matrix) into
+    /// the working `vertex_list`, then try to assemble it and its
+    /// predecessors into a polygon per the active strip/fan mode
+    fn add_vertex(&mut self) {
+        self.update_clip_mtx();
+
+        let v4 = [
+            self.current_vertex[0],
+            self.current_vertex[1],
+            self.current_vertex[2],
+            1 << 12,
+        ];
+        let coords = self.clip_mtx.transform_vec4(v4);
+
+        if self.polygon_type >= 2 && self.vertex_list_count as usize >= self.vertex_list.len() {
+            // Strips only look back at their last few vertices; slide the
+            // window down by two so a long strip keeps going with the same
+            // winding parity.
+            self.vertex_list.rotate_left(2);
+            self.vertex_list_count -= 2;
+        }
+
+        if self.vertex_list_count as usize >= self.vertex_list.len() {
+            // Separate triangles/quads reset after 3-4 vertices and strips
+            // are recycled above, so this is never reached; drop instead of
+            // panicking if it somehow is.
+            return;
+        }
+
+        let vertex = Vertex {
+            coords,
+            colors: [
+                (self.current_color & 0x1F) as i32,
+                ((self.current_color >> 5) & 0x1F) as i32,
+                ((self.current_color >> 10) & 0x1F) as i32,
+            ],
+            final_colors: [0; 3],
+            clipped: false,
+            texcoords: [
+                self.current_texcoords[0] as i32,
+                self.current_texcoords[1] as i32,
+            ],
+        };
+        self.vertex_list[self.vertex_list_count as usize] = vertex;
+        self.vertex_list_count += 1;
+
+        self.try_emit_polygon();
+    }
+
+    /// Once enough vertices have accumulated for the active polygon type,
+    /// clip the indicated vertices and hand them off to `add_polygon`
+    fn try_emit_polygon(&mut self) {
+        let count = self.vertex_list_count as usize;
+        let indices: Option<Vec<usize>> = match self.polygon_type {
+            0 if count == 3 => Some(vec![0, 1, 2]),
+            1 if count == 4 => Some(vec![0, 1, 2, 3]),
+            2 if count == 3 => Some(vec![0, 1, 2]),
+            2 if count > 3 => {
+                // Alternate winding every other triangle so the strip's
+                // faces stay consistently oriented.
+                let n = count - 1;
+                if (n - 2) % 2 == 0 {
+                    Some(vec![n - 2, n - 1, n])
+                } else {
+                    Some(vec![n - 1, n - 2, n])
+                }
+            }
+            3 if count == 4 => Some(vec![0, 1, 3, 2]),
+            3 if count >= 6 && count % 2 == 0 => {
+                let n = count - 1;
+                Some(vec![n - 3, n - 2, n, n - 1])
+            }
+            _ => None,
+        };
+
+        let Some(indices) = indices else {
+            return;
+        };
+
+        let ring: Vec<Vertex> = indices.iter().map(|&i| self.vertex_list[i].clone()).collect();
+        let clipped = self.clip(&ring);
+
+        // Separate triangles/quads fully reset once emitted; strips keep
+        // accumulating so the next vertex can extend them.
+        if self.polygon_type <= 1 {
+            self.vertex_list_count = 0;
+        }
+
+        if clipped.len() < 3 {
+            return;
+        }
+        self.add_polygon(&clipped);
+    }
+
+    /// Commit a clipped polygon's vertices into the geometry vertex/polygon
+    /// buffers, respecting the DS's fixed 6188-vertex/2048-polygon caps by
+    /// setting DISP3DCNT's RAM-overflow bit instead of panicking
+    fn add_polygon(&mut self, verts: &[Vertex]) {
+        let poly_idx = self.geo_poly_count as usize;
+        let vert_start = self.geo_vert_count as usize;
+
+        if poly_idx >= self.geo_poly.len() || vert_start + verts.len() > self.geo_vert.len() {
+            self.disp3dcnt.ram_overflow = true;
+            return;
+        }
+
+        let mut top_y = u16::MAX;
+        let mut bottom_y = 0u16;
+        for (i, v) in verts.iter().enumerate() {
+            self.geo_vert[vert_start + i] = v.clone();
+
+            let w = (v.coords[3].max(1)) as i64;
+            let y = v.coords[1] as i64;
+            let screen_y = (((w - y) * (SCANLINES as i64)) / (2 * w))
+                .clamp(0, SCANLINES as i64 - 1) as u16;
+            top_y = top_y.min(screen_y);
+            bottom_y = bottom_y.max(screen_y);
+        }
+
+        self.geo_poly[poly_idx] = Polygon {
+            vert_index: vert_start as u16,
+            vertices: verts.len() as u8,
+            top_y,
+            bottom_y,
+            attributes: self.current_poly_attr,
+            texparams: self.teximage_param,
+            palette_base: self.pltt_base,
+            translucent: (1..31).contains(&self.current_poly_attr.alpha),
+        };
+        self.geo_poly_count += 1;
+        self.geo_vert_count += verts.len() as i32;
     }
-    fn add_vertex(&mut self) {}
-    fn add_polygon(&mut self) {}
 
     fn request_fifo_dma(&mut self) {}
 }
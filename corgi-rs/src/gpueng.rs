@@ -46,6 +46,49 @@ pub struct DispCapCnt {
     pub enable_busy: bool,
 }
 
+/// A named bit-range within a 32-bit memory-mapped register, replacing
+/// open-coded `(word >> n) & mask` shifts with a single self-documenting
+/// field definition (mirrors the ioregs bitfield layer in rustboyadvance-ng)
+#[derive(Debug, Clone, Copy)]
+struct BitRange {
+    shift: u32,
+    width: u32,
+}
+
+impl BitRange {
+    const fn new(shift: u32, width: u32) -> Self {
+        BitRange { shift, width }
+    }
+
+    const fn mask(&self) -> u32 {
+        if self.width >= 32 {
+            u32::MAX
+        } else {
+            (1u32 << self.width) - 1
+        }
+    }
+
+    /// Read this field out of `word`
+    fn get(&self, word: u32) -> u32 {
+        (word >> self.shift) & self.mask()
+    }
+}
+
+/// Named bit ranges making up the DISPCAPCNT register (GBATEK 4000064h)
+mod dispcapcnt_bits {
+    use super::BitRange;
+    pub const EVA: BitRange = BitRange::new(0, 5);
+    pub const EVB: BitRange = BitRange::new(8, 5);
+    pub const VRAM_WRITE_BLOCK: BitRange = BitRange::new(16, 2);
+    pub const VRAM_WRITE_OFFSET: BitRange = BitRange::new(18, 2);
+    pub const CAPTURE_SIZE: BitRange = BitRange::new(20, 2);
+    pub const SRC_A_3D_ONLY: BitRange = BitRange::new(24, 1);
+    pub const SRC_B_DISPLAY_FIFO: BitRange = BitRange::new(25, 1);
+    pub const VRAM_READ_OFFSET: BitRange = BitRange::new(26, 2);
+    pub const CAPTURE_SOURCE: BitRange = BitRange::new(29, 2);
+    pub const ENABLE_BUSY: BitRange = BitRange::new(31, 1);
+}
+
 /// Window input enable registers
 #[derive(Debug, Clone, Default)]
 pub struct WinIn {
@@ -80,6 +123,208 @@ pub struct BldCnt {
     pub bd_second_target_pix: bool,
 }
 
+/// A single compositing layer's pixel, tagged with which layer produced it
+/// (0-3 = BG0-3, 4 = OBJ, 5 = backdrop, 0xFF = empty slot)
+#[derive(Debug, Clone, Copy)]
+pub struct LayerEntry {
+    pub color: u16,
+    pub source: u8,
+    pub priority: u8,
+    /// Set for OBJ mode 1 (semi-transparent) sprite pixels: forces alpha
+    /// blending with the entry beneath regardless of `bldcnt.effect`
+    pub semi_transparent: bool,
+}
+
+impl Default for LayerEntry {
+    fn default() -> Self {
+        LayerEntry {
+            color: 0,
+            source: 0xFF,
+            priority: 0xFF,
+            semi_transparent: false,
+        }
+    }
+}
+
+/// Source tags used in `LayerEntry::source` and `BldCnt` lookups
+pub const LAYER_OBJ: u8 = 4;
+pub const LAYER_BACKDROP: u8 = 5;
+
+/// Max layers tracked per pixel: 5 of the 6 possible sources (BG0-3, OBJ,
+/// backdrop), so the lowest-precedence one is dropped when all six cover it
+pub const LAYER_STACK_DEPTH: usize = 5;
+
+/// Fixed-capacity, priority-sorted list of the visible layers at one pixel,
+/// nearest (topmost) entry first - mirrors rustboyadvance-ng's `layer.rs`
+#[derive(Debug, Clone, Copy)]
+pub struct LayerStack {
+    pub entries: [LayerEntry; LAYER_STACK_DEPTH],
+    pub count: u8,
+}
+
+impl Default for LayerStack {
+    fn default() -> Self {
+        LayerStack {
+            entries: [LayerEntry::default(); LAYER_STACK_DEPTH],
+            count: 0,
+        }
+    }
+}
+
+/// Automatic frameskip policy, ported from PCSX-ReARMed's auto-frameskip heuristic
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FrameskipState {
+    /// -1 = auto, 0 = off, 1..=N = fixed skip count
+    pub set: i32,
+    /// Frames skipped since the last rendered frame
+    pub cnt: i32,
+    /// Whether the current frame is being skipped
+    pub active: bool,
+    /// Whether skipping is currently permitted (false while a display capture is in flight)
+    pub allow: bool,
+    /// Set once per frame when a frame was actually rendered
+    pub frame_ready: bool,
+    /// External advice from the frontend that the host is falling behind (auto mode only)
+    pub advice: bool,
+}
+
+/// Cursor over a frozen [`Gpu2DEngine`] byte block, mirroring the write side
+/// in [`Gpu2DEngine::freeze`] field for field
+struct FreezeReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> FreezeReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        FreezeReader { data, pos: 0 }
+    }
+
+    fn read_u8(&mut self) -> Result<u8, String> {
+        let byte = *self
+            .data
+            .get(self.pos)
+            .ok_or_else(|| "Gpu2DEngine freeze: truncated state block".to_string())?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn read_bool(&mut self) -> Result<bool, String> {
+        Ok(self.read_u8()? != 0)
+    }
+
+    fn read_u16(&mut self) -> Result<u16, String> {
+        let mut bytes = [0u8; 2];
+        for b in bytes.iter_mut() {
+            *b = self.read_u8()?;
+        }
+        Ok(u16::from_le_bytes(bytes))
+    }
+
+    fn read_i16(&mut self) -> Result<i16, String> {
+        Ok(self.read_u16()? as i16)
+    }
+
+    fn read_u32(&mut self) -> Result<u32, String> {
+        let mut bytes = [0u8; 4];
+        for b in bytes.iter_mut() {
+            *b = self.read_u8()?;
+        }
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    fn read_i32(&mut self) -> Result<i32, String> {
+        Ok(self.read_u32()? as i32)
+    }
+}
+
+fn push_u8(buf: &mut Vec<u8>, v: u8) {
+    buf.push(v);
+}
+fn push_bool(buf: &mut Vec<u8>, v: bool) {
+    buf.push(v as u8);
+}
+fn push_u16(buf: &mut Vec<u8>, v: u16) {
+    buf.extend_from_slice(&v.to_le_bytes());
+}
+fn push_i16(buf: &mut Vec<u8>, v: i16) {
+    buf.extend_from_slice(&v.to_le_bytes());
+}
+fn push_u32(buf: &mut Vec<u8>, v: u32) {
+    buf.extend_from_slice(&v.to_le_bytes());
+}
+fn push_i32(buf: &mut Vec<u8>, v: i32) {
+    buf.extend_from_slice(&v.to_le_bytes());
+}
+
+/// Identifies which of the DS's two LCD engines produced a tapped frame, so
+/// a recorder can stack the two screens correctly
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum FrameTag {
+    EngineA = 0,
+    EngineB = 1,
+}
+
+/// Which stage of the pipeline produced a tapped frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum FrameKind {
+    /// The final, post-composite on-screen image
+    Display = 0,
+    /// Raw output written by the DISPCAPCNT display-capture unit
+    Capture = 1,
+}
+
+/// Receives tapped frame data for recording, e.g. a video encoder. Frames
+/// are delivered as packed RGBA8 scanlines (`width * height * 4` bytes).
+pub trait FrameSink {
+    fn on_frame(&mut self, buf: &[u8], width: u32, height: u32, tag: FrameTag, kind: FrameKind);
+}
+
+/// Default [`FrameSink`] that accumulates tapped frames in memory and can
+/// flush them out as a raw, lossless stream for an external tool to
+/// transcode. Each flushed frame is framed as
+/// `tag(1) | kind(1) | width(4 LE) | height(4 LE) | RGBA8 pixels`.
+#[derive(Debug, Default)]
+pub struct RawFrameRecorder {
+    frames: Vec<(FrameTag, FrameKind, u32, u32, Vec<u8>)>,
+}
+
+impl RawFrameRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of frames accumulated since the last flush/clear
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Discard all accumulated frames without writing them out
+    pub fn clear(&mut self) {
+        self.frames.clear();
+    }
+
+    /// Write every accumulated frame out and clear the buffer
+    pub fn flush<W: std::io::Write>(&mut self, writer: &mut W) -> std::io::Result<()> {
+        for (tag, kind, width, height, buf) in &self.frames {
+            writer.write_all(&[*tag as u8, *kind as u8])?;
+            writer.write_all(&width.to_le_bytes())?;
+            writer.write_all(&height.to_le_bytes())?;
+            writer.write_all(buf)?;
+        }
+        self.frames.clear();
+        Ok(())
+    }
+}
+
+impl FrameSink for RawFrameRecorder {
+    fn on_frame(&mut self, buf: &[u8], width: u32, height: u32, tag: FrameTag, kind: FrameKind) {
+        self.frames.push((tag, kind, width, height, buf.to_vec()));
+    }
+}
+
 /// 2D GPU engine
 pub struct Gpu2DEngine {
     gpu: Option<Arc<Mutex<crate::gpu::Gpu>>>,
@@ -88,6 +333,16 @@ pub struct Gpu2DEngine {
     front_framebuffer: Vec<u32>,
     final_bg_priority: Vec<u8>,
     sprite_scanline: Vec<u32>,
+
+    /// Registered recording sink; receives both the composited display
+    /// output and the raw DISPCAPCNT capture output
+    frame_sink: Option<Box<dyn FrameSink>>,
+    /// Accumulates the in-progress DISPCAPCNT capture so the whole captured
+    /// frame can be tapped once the capture completes
+    capture_buffer: Vec<u16>,
+
+    /// Per-pixel priority-sorted layer stack feeding the SFX pass
+    layer_stacks: Vec<LayerStack>,
     window_mask: Vec<u8>,
     engine_a: bool,
 
@@ -130,6 +385,8 @@ pub struct Gpu2DEngine {
 
     master_bright: u16,
     disp_capcnt_val: u32,
+
+    frameskip: FrameskipState,
 }
 
 impl Gpu2DEngine {
@@ -142,6 +399,9 @@ impl Gpu2DEngine {
             front_framebuffer: vec![0u32; size],
             final_bg_priority: vec![0u8; size * 2],
             sprite_scanline: vec![0u32; size * 2],
+            frame_sink: None,
+            capture_buffer: Vec::new(),
+            layer_stacks: vec![LayerStack::default(); PIXELS_PER_LINE as usize],
             window_mask: vec![0u8; PIXELS_PER_LINE as usize],
             engine_a,
             disp_cnt: DispCnt::default(),
@@ -176,6 +436,8 @@ impl Gpu2DEngine {
             bldy: 0,
             master_bright: 0,
             disp_capcnt_val: 0,
+
+            frameskip: FrameskipState::default(),
         }
     }
 
@@ -187,19 +449,426 @@ impl Gpu2DEngine {
         let gpu_arc = self.gpu.as_ref().unwrap();
         let gpu = gpu_arc.lock().unwrap();
         let palette = gpu.get_palette(self.engine_a);
-        let vcount = gpu.get_VCOUNT() as usize;
-        let scanline = vcount * PIXELS_PER_LINE as usize;
         let base_color = palette[0];
-        let r = ((base_color & 0x1F) << 3) as u32;
-        let g = (((base_color >> 5) & 0x1F) << 3) as u32;
-        let b = (((base_color >> 10) & 0x1F) << 3) as u32;
-        let color = 0xFF000000u32 | (r << 16) | (g << 8) | b;
         for x in 0..(PIXELS_PER_LINE as usize) {
-            self.framebuffer[x + scanline] = color;
+            self.insert_layer(x, base_color, LAYER_BACKDROP, 4, false);
+        }
+    }
+
+    /// Convert a 15-bit BGR555 color to 0xFFRRGGBB
+    fn color15_to_argb(color16: u16) -> u32 {
+        let r = ((color16 & 0x1F) << 3) as u32;
+        let g = (((color16 >> 5) & 0x1F) << 3) as u32;
+        let b = (((color16 >> 10) & 0x1F) << 3) as u32;
+        0xFF000000u32 | (r << 16) | (g << 8) | b
+    }
+
+    /// Convert a 15-bit BGR555 color directly to RGBA8 bytes for the frame tap
+    fn color15_to_rgba_bytes(color16: u16) -> [u8; 4] {
+        let r = ((color16 & 0x1F) << 3) as u8;
+        let g = (((color16 >> 5) & 0x1F) << 3) as u8;
+        let b = (((color16 >> 10) & 0x1F) << 3) as u8;
+        [r, g, b, 0xFF]
+    }
+
+    /// Rank used to break priority ties: OBJ sits above all BGs, then BG0..BG3
+    /// in ascending order, with the backdrop always last
+    fn layer_rank(source: u8) -> u8 {
+        match source {
+            LAYER_OBJ => 0,
+            0..=3 => 1 + source,
+            LAYER_BACKDROP => 5,
+            _ => 6,
+        }
+    }
+
+    /// Insert a newly-drawn, non-transparent pixel into `x`'s layer stack in
+    /// priority order (lower `priority` value = nearer the viewer). When the
+    /// stack is full, the lowest-precedence entry is dropped, which is the new
+    /// pixel itself if it ranks below everything already in the stack
+    fn insert_layer(&mut self, x: usize, color: u16, source: u8, priority: u8, semi_transparent: bool) {
+        let stack = &mut self.layer_stacks[x];
+        let key = (priority, Self::layer_rank(source));
+
+        let mut insert_at = stack.count as usize;
+        for i in 0..stack.count as usize {
+            let existing = stack.entries[i];
+            if key < (existing.priority, Self::layer_rank(existing.source)) {
+                insert_at = i;
+                break;
+            }
+        }
+
+        if insert_at >= LAYER_STACK_DEPTH {
+            return;
+        }
+
+        let last = (stack.count as usize).min(LAYER_STACK_DEPTH - 1);
+        let mut i = last;
+        while i > insert_at {
+            stack.entries[i] = stack.entries[i - 1];
+            i -= 1;
+        }
+        stack.entries[insert_at] = LayerEntry {
+            color,
+            source,
+            priority,
+            semi_transparent,
+        };
+        stack.count = (stack.count + 1).min(LAYER_STACK_DEPTH as u8);
+    }
+
+    /// Whether the given layer source is a first (upper) blend target per `bldcnt`
+    fn is_first_target(&self, source: u8) -> bool {
+        match source {
+            0..=3 => self.bldcnt.bg_first_target_pix[source as usize],
+            LAYER_OBJ => self.bldcnt.obj_first_target_pix,
+            LAYER_BACKDROP => self.bldcnt.bd_first_target_pix,
+            _ => false,
+        }
+    }
+
+    /// Whether the given layer source is a second (lower) blend target per `bldcnt`
+    fn is_second_target(&self, source: u8) -> bool {
+        match source {
+            0..=3 => self.bldcnt.bg_second_target_pix[source as usize],
+            LAYER_OBJ => self.bldcnt.obj_second_target_pix,
+            LAYER_BACKDROP => self.bldcnt.bd_second_target_pix,
+            _ => false,
+        }
+    }
+
+    /// Alpha-blend two 15-bit colors per channel: `min(31, top*eva/16 + bottom*evb/16)`
+    fn blend_alpha(top: u16, bottom: u16, bldalpha: u16) -> u16 {
+        let eva = (bldalpha & 0x1F).min(16) as u32;
+        let evb = ((bldalpha >> 8) & 0x1F).min(16) as u32;
+
+        let channel = |shift: u32| -> u32 {
+            let t = ((top as u32) >> shift) & 0x1F;
+            let b = ((bottom as u32) >> shift) & 0x1F;
+            ((t * eva + b * evb) / 16).min(31)
+        };
+
+        (channel(0) | (channel(5) << 5) | (channel(10) << 10)) as u16
+    }
+
+    /// Brightness increase: `c + (31-c)*evy/16` per channel
+    fn blend_brightness_up(color: u16, bldy: u8) -> u16 {
+        let evy = ((bldy as u32) & 0x1F).min(16);
+        let channel = |shift: u32| -> u32 {
+            let c = ((color as u32) >> shift) & 0x1F;
+            (c + (31 - c) * evy / 16).min(31)
+        };
+        (channel(0) | (channel(5) << 5) | (channel(10) << 10)) as u16
+    }
+
+    /// Brightness decrease: `c - c*evy/16` per channel
+    fn blend_brightness_down(color: u16, bldy: u8) -> u16 {
+        let evy = ((bldy as u32) & 0x1F).min(16);
+        let channel = |shift: u32| -> u32 {
+            let c = ((color as u32) >> shift) & 0x1F;
+            c.saturating_sub(c * evy / 16)
+        };
+        (channel(0) | (channel(5) << 5) | (channel(10) << 10)) as u16
+    }
+
+    /// Apply the BLDCNT/BLDALPHA/BLDY color special effects pass, reading the
+    /// top/under layer records built up by the BG/OBJ/backdrop draws and
+    /// writing the final composited color into `framebuffer`
+    pub fn apply_color_effects(&mut self) {
+        let vcount = if let Some(g) = &self.gpu {
+            g.lock().unwrap().get_VCOUNT()
+        } else {
+            0
+        } as usize;
+        let line = vcount * PIXELS_PER_LINE as usize;
+
+        for x in 0..(PIXELS_PER_LINE as usize) {
+            let stack = self.layer_stacks[x];
+
+            // A pixel with an empty stack was drawn directly into the
+            // framebuffer by a path that bypasses the layer stack (e.g. the
+            // 3D engine's BG0 scanline) - leave it untouched.
+            if stack.count == 0 {
+                continue;
+            }
+
+            let top = stack.entries[0];
+            let under = if stack.count > 1 {
+                Some(stack.entries[1])
+            } else {
+                None
+            };
+
+            // A semi-transparent OBJ pixel forces an alpha blend with the
+            // entry beneath it, regardless of bldcnt.effect or the window's
+            // color-special-effect bit, using the sprite as the first target.
+            let color16 = if top.semi_transparent {
+                match under {
+                    Some(under) if self.is_second_target(under.source) => {
+                        Self::blend_alpha(top.color, under.color, self.bldalpha)
+                    }
+                    _ => top.color,
+                }
+            } else if (self.window_mask[x] & (1 << 5)) == 0 {
+                top.color
+            } else {
+                match (self.bldcnt.effect, under) {
+                    (1, Some(under))
+                        if self.is_first_target(top.source) && self.is_second_target(under.source) =>
+                    {
+                        Self::blend_alpha(top.color, under.color, self.bldalpha)
+                    }
+                    (2, _) if self.is_first_target(top.source) => {
+                        Self::blend_brightness_up(top.color, self.bldy)
+                    }
+                    (3, _) if self.is_first_target(top.source) => {
+                        Self::blend_brightness_down(top.color, self.bldy)
+                    }
+                    _ => top.color,
+                }
+            };
+
+            self.framebuffer[x + line] = Self::color15_to_argb(color16);
+        }
+    }
+
+    /// Convert a composited 0xFFRRGGBB pixel back to 15-bit BGR555
+    fn argb_to_color15(argb: u32) -> u16 {
+        let r = (((argb >> 16) & 0xFF) >> 3) as u16;
+        let g = (((argb >> 8) & 0xFF) >> 3) as u16;
+        let b = ((argb & 0xFF) >> 3) as u16;
+        r | (g << 5) | (b << 10)
+    }
+
+    /// Apply the MASTER_BRIGHT brightness up/down pass to the current
+    /// scanline: bits 14-15 select the mode (0 = none, 1 = up, 2 = down),
+    /// bits 0-4 give the factor clamped to 16
+    pub fn apply_master_bright(&mut self) {
+        let mode = (self.master_bright >> 14) & 0x3;
+        if mode != 1 && mode != 2 {
+            return;
+        }
+        let factor = (self.master_bright & 0x1F) as u8;
+
+        let vcount = if let Some(g) = &self.gpu {
+            g.lock().unwrap().get_VCOUNT()
+        } else {
+            0
+        } as usize;
+        let line = vcount * (PIXELS_PER_LINE as usize);
+
+        for x in 0..(PIXELS_PER_LINE as usize) {
+            let color16 = Self::argb_to_color15(self.framebuffer[x + line]);
+            let adjusted = if mode == 1 {
+                Self::blend_brightness_up(color16, factor)
+            } else {
+                Self::blend_brightness_down(color16, factor)
+            };
+            self.framebuffer[x + line] = Self::color15_to_argb(adjusted);
+        }
+    }
+
+    /// Run the DISPCAPCNT display-capture unit for the current scanline.
+    /// Only engine A feeds the capture unit; call once per visible line
+    /// while `disp_capcnt.enable_busy` is set.
+    ///
+    /// `disp_capcnt.a_3d_only` is ignored: Source A is always the full
+    /// engine-A composite, never the 3D layer on its own, because the 3D
+    /// engine writes straight into `framebuffer` without tagging pixels in
+    /// `layer_stacks`.
+    pub fn run_display_capture(&mut self) {
+        if !self.engine_a || !self.disp_capcnt.enable_busy || self.gpu.is_none() {
+            return;
+        }
+
+        let (capture_w, capture_h): (i32, i32) = match self.disp_capcnt.capture_size {
+            0 => (128, 128),
+            1 => (256, 64),
+            2 => (256, 128),
+            3 => (256, 192),
+            _ => (128, 128),
+        };
+
+        let vcount = if let Some(g) = &self.gpu {
+            g.lock().unwrap().get_VCOUNT()
+        } else {
+            0
+        } as i32;
+        if vcount >= capture_h {
+            return;
+        }
+
+        if vcount == 0 || self.capture_buffer.len() != (capture_w * capture_h) as usize {
+            self.capture_buffer = vec![0u16; (capture_w * capture_h) as usize];
+        }
+
+        let gpu_arc = self.gpu.as_ref().unwrap().clone();
+        let mut gpu = gpu_arc.lock().unwrap();
+
+        let read_offset_pixels = (self.disp_capcnt.vram_read_offset as usize) * 0x4000;
+        let write_offset_pixels = (self.disp_capcnt.vram_write_offset as usize) * 0x4000;
+        let line = (vcount as usize) * (PIXELS_PER_LINE as usize);
+
+        // BLDALPHA-style packing so the existing alpha-blend helper can be reused.
+        let capture_alpha = (self.disp_capcnt.eva as u16 & 0x1F) | ((self.disp_capcnt.evb as u16 & 0x1F) << 8);
+
+        // `a_3d_only` has no effect: the 3D engine writes straight into
+        // `framebuffer` without tagging its pixels in the layer stack (see
+        // draw_scanline), so there is no 3D-only line to read. Source A is
+        // the full engine A output either way.
+        let _ = self.disp_capcnt.a_3d_only;
+
+        for x in 0..(capture_w as usize) {
+            let a_color = Self::argb_to_color15(self.framebuffer[x + line]);
+            // Bit 15 carries Source A's alpha: clear only where nothing but
+            // the backdrop was visible at this pixel.
+            let a_alpha: u16 = if self.layer_stacks[x].entries[0].source == LAYER_BACKDROP {
+                0
+            } else {
+                1 << 15
+            };
+
+            let b_color = if self.disp_capcnt.b_display_fifo {
+                // Display FIFO capture source is not modeled; treat as black.
+                0
+            } else {
+                let src_block = gpu.get_VRAM_block(self.disp_cnt.vram_block as usize);
+                let src_idx = (read_offset_pixels + x + line) % src_block.len().max(1);
+                src_block[src_idx]
+            };
+
+            let out_color = match self.disp_capcnt.capture_source {
+                0 => a_color,
+                1 => b_color,
+                _ => Self::blend_alpha(a_color, b_color, capture_alpha),
+            };
+
+            let dest_block = gpu.get_VRAM_block_mut(self.disp_capcnt.vram_write_block as usize);
+            let dest_idx = (write_offset_pixels + x + (vcount as usize) * (capture_w as usize))
+                % dest_block.len().max(1);
+            dest_block[dest_idx] = (out_color & 0x7FFF) | a_alpha;
+
+            self.capture_buffer[x + (vcount as usize) * (capture_w as usize)] = out_color & 0x7FFF;
+        }
+
+        self.captured_lines += 1;
+        if self.captured_lines >= capture_h {
+            drop(gpu);
+            self.tap_capture_frame(capture_w as u32, capture_h as u32);
+
+            self.disp_capcnt.enable_busy = false;
+            self.disp_capcnt_val &= !(1u32 << 31);
+            self.captured_lines = 0;
+        }
+    }
+
+    /// Whether `vcount` falls within a `[y1, y2)` window range, wrapping
+    /// around the screen when `y2 < y1`; `y2` beyond the visible area is
+    /// clamped to the bottom of the screen
+    fn in_window_v(y1: u8, y2: u8, vcount: usize) -> bool {
+        let y2 = (y2 as usize).min(SCANLINES as usize);
+        if y2 >= y1 as usize {
+            (y1 as usize) <= vcount && vcount < y2
+        } else {
+            vcount >= y1 as usize || vcount < y2
+        }
+    }
+
+    /// Whether `x` falls within a `[x1, x2)` window range, wrapping around
+    /// the line when `x2 < x1`; `x2` beyond the visible area is clamped to
+    /// the right edge of the screen
+    fn in_window_h(x1: u8, x2: u8, x: usize) -> bool {
+        let width = PIXELS_PER_LINE as usize;
+        let x2 = (x2 as usize).min(width);
+        if x2 >= x1 as usize {
+            (x1 as usize) <= x && x < x2
+        } else {
+            x >= x1 as usize || x < x2
+        }
+    }
+
+    /// Pack a window region's BG/OBJ/color-special enable bits into the
+    /// `window_mask` byte layout (bits 0-3 = BG0-3, bit 4 = OBJ, bit 5 = SFX)
+    fn pack_window_region(bg_enabled: &[bool; 4], obj_enabled: bool, color_special: bool) -> u8 {
+        let mut mask = 0u8;
+        for (bit, enabled) in bg_enabled.iter().enumerate() {
+            if *enabled {
+                mask |= 1 << bit;
+            }
+        }
+        if obj_enabled {
+            mask |= 1 << 4;
+        }
+        if color_special {
+            mask |= 1 << 5;
+        }
+        mask
+    }
+
+    /// Compute the per-pixel `window_mask` for the current scanline from
+    /// WIN0/WIN1/OBJ-window, following the WIN0 > WIN1 > OBJ-window > outside
+    /// priority used by the real PPU
+    fn get_window_mask_internal(&mut self) {
+        let vcount = if let Some(g) = &self.gpu {
+            g.lock().unwrap().get_VCOUNT()
+        } else {
+            0
+        } as usize;
+
+        let win0v_y1 = (self.win0v >> 8) as u8;
+        let win0v_y2 = (self.win0v & 0xFF) as u8;
+        let win0h_x1 = (self.win0h >> 8) as u8;
+        let win0h_x2 = (self.win0h & 0xFF) as u8;
+        let win1v_y1 = (self.win1v >> 8) as u8;
+        let win1v_y2 = (self.win1v & 0xFF) as u8;
+        let win1h_x1 = (self.win1h >> 8) as u8;
+        let win1h_x2 = (self.win1h & 0xFF) as u8;
+
+        let win0_v_active = self.disp_cnt.display_win0 && Self::in_window_v(win0v_y1, win0v_y2, vcount);
+        let win1_v_active = self.disp_cnt.display_win1 && Self::in_window_v(win1v_y1, win1v_y2, vcount);
+        let any_window = self.disp_cnt.display_win0 || self.disp_cnt.display_win1 || self.disp_cnt.obj_win_display;
+
+        for x in 0..(PIXELS_PER_LINE as usize) {
+            let win0_active = win0_v_active && Self::in_window_h(win0h_x1, win0h_x2, x);
+            let win1_active = win1_v_active && Self::in_window_h(win1h_x1, win1h_x2, x);
+            // OBJ-window coverage is contributed by sprites in OBJ mode 2,
+            // which mark themselves in sprite_scanline bit 19 rather than
+            // drawing color (see scan_oam).
+            let objwin_active = self.disp_cnt.obj_win_display && (self.sprite_scanline[x] & (1 << 19)) != 0;
+
+            self.window_mask[x] = if win0_active {
+                Self::pack_window_region(
+                    &self.winin.win0_bg_enabled,
+                    self.winin.win0_obj_enabled,
+                    self.winin.win0_color_special,
+                )
+            } else if win1_active {
+                Self::pack_window_region(
+                    &self.winin.win1_bg_enabled,
+                    self.winin.win1_obj_enabled,
+                    self.winin.win1_color_special,
+                )
+            } else if objwin_active {
+                Self::pack_window_region(
+                    &self.winout.objwin_bg_enabled,
+                    self.winout.objwin_obj_enabled,
+                    self.winout.objwin_color_special,
+                )
+            } else if any_window {
+                Self::pack_window_region(
+                    &self.winout.outside_bg_enabled,
+                    self.winout.outside_obj_enabled,
+                    self.winout.outside_color_special,
+                )
+            } else {
+                0xFF
+            };
         }
     }
 
-    /// Draw background text layer (simplified port of C++ implementation)
+    /// Draw background text layer: decode the scrolled 32x32-cell tilemap,
+    /// honoring wide/tall screenblocks, H/V flip, and 4bpp/8bpp tiles
     pub fn draw_bg_txt(&mut self, index: usize) {
         if self.gpu.is_none() {
             return;
@@ -207,40 +876,114 @@ impl Gpu2DEngine {
         let gpu_arc = self.gpu.as_ref().unwrap();
         let gpu = gpu_arc.lock().unwrap();
 
-        let mut x_offset = self.bghofs[index] as u32;
-        let y_offset = (self.bgvofs[index] as u32).wrapping_add(gpu.get_VCOUNT());
+        let vcount = gpu.get_VCOUNT();
+        let bg_mosaic = (self.bgcnt[index] & (1 << 6)) != 0;
+        let bg_v_mosaic_size = ((self.mosaic >> 4) & 0xF) as u32 + 1;
+        let bg_h_mosaic_size = (self.mosaic & 0xF) as u32 + 1;
+        let mosaic_vcount = if bg_mosaic {
+            vcount - (vcount % bg_v_mosaic_size)
+        } else {
+            vcount
+        };
+        let y = (self.bgvofs[index] as u32).wrapping_add(mosaic_vcount);
         let palette = gpu.get_palette(self.engine_a);
 
         let one_palette_mode = (self.bgcnt[index] & (1 << 7)) != 0;
+        let screen_size = (self.bgcnt[index] >> 14) & 0x3;
+        let (map_w_tiles, map_h_tiles): (u32, u32) = match screen_size {
+            0 => (32, 32),
+            1 => (64, 32),
+            2 => (32, 64),
+            3 => (64, 64),
+            _ => (32, 32),
+        };
 
-        // Determine screen_base / char_base (approximation)
-        let (mut screen_base, mut char_base) = if self.engine_a {
+        // Determine screen_base / char_base
+        let (screen_base_vram, char_base_vram) = if self.engine_a {
             (
                 crate::memconsts::VRAM_BGA_START
-                    + ((self.disp_cnt.screen_base as usize) * 1024 * 64) as usize,
-                crate::memconsts::VRAM_BGA_START
-                    + ((self.disp_cnt.char_base as usize) * 1024 * 64) as usize,
+                    + ((self.disp_cnt.screen_base as usize) * 1024 * 64),
+                crate::memconsts::VRAM_BGA_START + ((self.disp_cnt.char_base as usize) * 1024 * 64),
             )
         } else {
             (crate::memconsts::VRAM_BGB_C, crate::memconsts::VRAM_BGB_C)
         };
 
-        screen_base = screen_base + (((self.bgcnt[index] >> 8) & 0x1F) as usize) * 1024 * 2;
-        char_base = char_base + (((self.bgcnt[index] >> 2) & 0xF) as usize) * 1024 * 16;
+        let screen_base =
+            screen_base_vram + (((self.bgcnt[index] >> 8) & 0x1F) as usize) * 1024 * 2;
+        let char_base = char_base_vram + (((self.bgcnt[index] >> 2) & 0xF) as usize) * 1024 * 16;
 
-        let scanline = (gpu.get_VCOUNT() as usize) * (PIXELS_PER_LINE as usize);
+        let tile_y = (y / 8) % map_h_tiles;
+        let pixel_y_in_tile = (y % 8) as usize;
+        let sb_y = tile_y / 32;
+        let local_tile_y = tile_y % 32;
 
-        // Very small, partial implementation: sample tiles and write pixels if non-zero
         for pixel in 0..(PIXELS_PER_LINE as usize) {
-            // For performance/simplicity, produce a transparent pixel if palette index 0
-            let color16 = palette[0];
-            let true_color = 0xFF000000u32
-                | ((((color16 & 0x1F) << 3) as u32) << 16)
-                | ((((color16 >> 5) & 0x1F) << 3) as u32) << 8
-                | ((((color16 >> 10) & 0x1F) << 3) as u32);
-            self.framebuffer[pixel + scanline] = true_color;
-            self.final_bg_priority[pixel] = (self.bgcnt[index] & 0x3) as u8;
-            x_offset = x_offset.wrapping_add(1);
+            let mosaic_pixel = if bg_mosaic {
+                pixel as u32 - (pixel as u32 % bg_h_mosaic_size)
+            } else {
+                pixel as u32
+            };
+            let x = (self.bghofs[index] as u32).wrapping_add(mosaic_pixel);
+            let tile_x = (x / 8) % map_w_tiles;
+            let pixel_x_in_tile = (x % 8) as usize;
+            let sb_x = tile_x / 32;
+            let local_tile_x = tile_x % 32;
+
+            // Select the correct 2KB screenblock when the map is wider/taller than 32x32
+            let screenblock_index = match screen_size {
+                0 => 0,
+                1 => sb_x,
+                2 => sb_y,
+                3 => sb_y * 2 + sb_x,
+                _ => 0,
+            };
+
+            let map_entry_addr = screen_base
+                + (screenblock_index as usize) * 2048
+                + ((local_tile_y * 32 + local_tile_x) as usize) * 2;
+            let entry = gpu.read_vram16(map_entry_addr);
+
+            let tile_num = (entry & 0x3FF) as usize;
+            let h_flip = (entry & (1 << 10)) != 0;
+            let v_flip = (entry & (1 << 11)) != 0;
+            let pal_bank = ((entry >> 12) & 0xF) as usize;
+
+            let px = if h_flip { 7 - pixel_x_in_tile } else { pixel_x_in_tile };
+            let py = if v_flip { 7 - pixel_y_in_tile } else { pixel_y_in_tile };
+
+            let color16 = if one_palette_mode {
+                let tile_addr = char_base + tile_num * 64 + py * 8 + px;
+                let color_index = gpu.read_vram8(tile_addr) as usize;
+                if color_index == 0 {
+                    continue;
+                }
+                if self.disp_cnt.bg_extended_palette {
+                    gpu.get_bg_ext_palette(self.engine_a, pal_bank)[color_index]
+                } else {
+                    palette[color_index]
+                }
+            } else {
+                let tile_addr = char_base + tile_num * 32 + py * 4 + px / 2;
+                let byte = gpu.read_vram8(tile_addr);
+                let color_index = if px % 2 == 0 {
+                    (byte & 0xF) as usize
+                } else {
+                    (byte >> 4) as usize
+                };
+                if color_index == 0 {
+                    continue;
+                }
+                palette[pal_bank * 16 + color_index]
+            };
+
+            if (self.window_mask[pixel] & (1 << index)) == 0 {
+                continue;
+            }
+
+            let priority = (self.bgcnt[index] & 0x3) as u8;
+            self.insert_layer(pixel, color16, index as u8, priority, false);
+            self.final_bg_priority[pixel] = priority;
         }
     }
 
@@ -250,34 +993,314 @@ impl Gpu2DEngine {
         self.draw_bg_txt(index);
     }
 
-    /// Draw sprites for current scanline (partial implementation)
-    pub fn draw_sprites(&mut self) {
-        // Very partial port: composite sprite_scanline into framebuffer when present
-        let vcount = if let Some(g) = &self.gpu {
-            g.lock().unwrap().get_VCOUNT()
+    /// Width/height in pixels for an OBJ's `shape`/`size` attribute pair
+    fn obj_dimensions(shape: u16, size: u16) -> (i32, i32) {
+        match (shape, size) {
+            (0, 0) => (8, 8),
+            (0, 1) => (16, 16),
+            (0, 2) => (32, 32),
+            (0, 3) => (64, 64),
+            (1, 0) => (16, 8),
+            (1, 1) => (32, 8),
+            (1, 2) => (32, 16),
+            (1, 3) => (64, 32),
+            (2, 0) => (8, 16),
+            (2, 1) => (8, 32),
+            (2, 2) => (16, 32),
+            (2, 3) => (32, 64),
+            _ => (8, 8),
+        }
+    }
+
+    /// Map an on-screen offset within an affine sprite's bounding box into
+    /// texture space via its 2x2 OAM matrix (8.8 fixed point), returning
+    /// `None` when the sample falls outside the sprite's actual tile bounds
+    fn draw_rotscale_sprite(
+        sx: i32,
+        sy: i32,
+        bb_w: i32,
+        bb_h: i32,
+        width: i32,
+        height: i32,
+        pa: i16,
+        pb: i16,
+        pc: i16,
+        pd: i16,
+    ) -> Option<(i32, i32)> {
+        let rel_x = sx - bb_w / 2;
+        let rel_y = sy - bb_h / 2;
+        let tex_x = ((rel_x * pa as i32 + rel_y * pb as i32) >> 8) + width / 2;
+        let tex_y = ((rel_x * pc as i32 + rel_y * pd as i32) >> 8) + height / 2;
+        if tex_x < 0 || tex_x >= width || tex_y < 0 || tex_y >= height {
+            None
         } else {
-            0
-        } as usize;
-        let line = vcount * PIXELS_PER_LINE as usize;
+            Some((tex_x, tex_y))
+        }
+    }
+
+    /// Scan the 128 OAM entries and rebuild `sprite_scanline` for the current
+    /// VCOUNT, resolving affine (rot/scale) sprites, OBJ window coverage, and
+    /// OAM-index priority ties, following rustboyadvance-ng's `render/obj.rs`
+    ///
+    /// `sprite_scanline` packing: bits 0-15 = BGR555 color, bits 16-17 =
+    /// OBJ priority, bit 18 = semi-transparent (OBJ mode 1), bit 19 =
+    /// OBJ-window coverage (OBJ mode 2), bit 31 = drawn
+    pub fn scan_oam(&mut self) {
+        for entry in self.sprite_scanline.iter_mut().take(PIXELS_PER_LINE as usize) {
+            *entry = 0;
+        }
+
+        if self.gpu.is_none() {
+            return;
+        }
+        let gpu_arc = self.gpu.as_ref().unwrap().clone();
+        let gpu = gpu_arc.lock().unwrap();
+
+        let vcount = gpu.get_VCOUNT() as i32;
+        let oam = gpu.get_oam(self.engine_a);
+        let palette = gpu.get_obj_palette(self.engine_a);
+        let obj_base = if self.engine_a {
+            crate::memconsts::VRAM_OBJA_START
+        } else {
+            crate::memconsts::VRAM_OBJB_START
+        };
+
+        let obj_h_mosaic_size = (((self.mosaic >> 8) & 0xF) + 1) as i32;
+        let obj_v_mosaic_size = (((self.mosaic >> 12) & 0xF) + 1) as i32;
+
+        // Tracks, per pixel, the priority of the sprite currently claiming it
+        // so lower-OAM-index sprites win ties against later ones of equal priority.
+        let mut claimed_priority = [i32::MAX; 256];
+        let mut objwin_hit = [false; 256];
+
+        for oam_index in 0..128usize {
+            let base = oam_index * 4;
+            let attr0 = oam[base];
+            let attr1 = oam[base + 1];
+            let attr2 = oam[base + 2];
+
+            let affine = (attr0 & (1 << 8)) != 0;
+            let double_size_or_disable = (attr0 & (1 << 9)) != 0;
+            if !affine && double_size_or_disable {
+                continue;
+            }
+            let double_size = affine && double_size_or_disable;
+
+            let mode = ((attr0 >> 10) & 0x3) as u8;
+            let color_256 = (attr0 & (1 << 13)) != 0;
+            let shape = (attr0 >> 14) & 0x3;
+
+            let y_raw = (attr0 & 0xFF) as i32;
+            let y = if y_raw >= SCANLINES as i32 { y_raw - 256 } else { y_raw };
+
+            let size = (attr1 >> 14) & 0x3;
+            let (width, height) = Self::obj_dimensions(shape, size);
+            let (bb_w, bb_h) = if double_size { (width * 2, height * 2) } else { (width, height) };
+
+            if vcount < y || vcount >= y + bb_h {
+                continue;
+            }
+
+            let x_raw = (attr1 & 0x1FF) as i32;
+            let x = if x_raw >= 256 { x_raw - 512 } else { x_raw };
+
+            let tile_number = (attr2 & 0x3FF) as i32;
+            let priority = ((attr2 >> 10) & 0x3) as u8;
+            let pal_bank = ((attr2 >> 12) & 0xF) as usize;
+            let obj_mosaic = (attr0 & (1 << 12)) != 0;
+
+            let (pa, pb, pc, pd, h_flip, v_flip) = if affine {
+                let group = ((attr1 >> 9) & 0x1F) as usize;
+                let pa = oam[(group * 4) * 4 + 3] as i16;
+                let pb = oam[(group * 4 + 1) * 4 + 3] as i16;
+                let pc = oam[(group * 4 + 2) * 4 + 3] as i16;
+                let pd = oam[(group * 4 + 3) * 4 + 3] as i16;
+                (pa, pb, pc, pd, false, false)
+            } else {
+                (0, 0, 0, 0, (attr1 & (1 << 12)) != 0, (attr1 & (1 << 13)) != 0)
+            };
+
+            let mut sy = vcount - y;
+            if obj_mosaic {
+                sy -= sy % obj_v_mosaic_size;
+            }
+            let tiles_w = width / 8;
+            let row_stride_tiles = if self.disp_cnt.tile_obj_1d { tiles_w } else { 32 };
+            // OBJ tile numbers are always addressed at a fixed 32-byte
+            // granularity, unlike BG text-mode tiles; a 256-color sprite
+            // just needs twice as many of them per row/column, since each
+            // of its tiles spans two consecutive 32-byte slots.
+            let tile_stride_bytes = 32;
+            let stride_mul = if color_256 { 2 } else { 1 };
+
+            for screen_x in x.max(0)..(x + bb_w).min(PIXELS_PER_LINE as i32) {
+                let px = screen_x as usize;
+                let mut sx = screen_x - x;
+                if obj_mosaic {
+                    sx -= sx % obj_h_mosaic_size;
+                }
+
+                let (tex_x, tex_y) = if affine {
+                    match Self::draw_rotscale_sprite(sx, sy, bb_w, bb_h, width, height, pa, pb, pc, pd) {
+                        Some(t) => t,
+                        None => continue,
+                    }
+                } else {
+                    let tx = if h_flip { width - 1 - sx } else { sx };
+                    let ty = if v_flip { height - 1 - sy } else { sy };
+                    (tx, ty)
+                };
+
+                if mode == 2 {
+                    objwin_hit[px] = true;
+                    continue;
+                }
+                if mode == 3 {
+                    // OBJ bitmap mode is not supported by this renderer yet.
+                    continue;
+                }
+
+                let actual_tile =
+                    tile_number + (tex_y / 8) * row_stride_tiles * stride_mul + (tex_x / 8) * stride_mul;
+                let tile_addr = obj_base + (actual_tile as usize) * tile_stride_bytes;
+                let inner_x = (tex_x % 8) as usize;
+                let inner_y = (tex_y % 8) as usize;
+
+                let color_index = if color_256 {
+                    gpu.read_vram8(tile_addr + inner_y * 8 + inner_x) as usize
+                } else {
+                    let byte = gpu.read_vram8(tile_addr + inner_y * 4 + inner_x / 2);
+                    if inner_x % 2 == 0 {
+                        (byte & 0xF) as usize
+                    } else {
+                        (byte >> 4) as usize
+                    }
+                };
+                if color_index == 0 {
+                    continue;
+                }
+
+                if (priority as i32) >= claimed_priority[px] {
+                    continue;
+                }
+                claimed_priority[px] = priority as i32;
+
+                let color16 = if color_256 {
+                    if self.disp_cnt.obj_extended_palette {
+                        gpu.get_obj_ext_palette(self.engine_a, pal_bank)[color_index]
+                    } else {
+                        palette[color_index]
+                    }
+                } else {
+                    palette[pal_bank * 16 + color_index]
+                };
+
+                let mut packed = (1u32 << 31) | (color16 as u32) | ((priority as u32) << 16);
+                if mode == 1 {
+                    packed |= 1 << 18;
+                }
+                self.sprite_scanline[px] = packed;
+            }
+        }
+
+        for (px, hit) in objwin_hit.iter().enumerate() {
+            if *hit {
+                self.sprite_scanline[px] |= 1 << 19;
+            }
+        }
+    }
+
+    /// Draw sprites for current scanline: composite the OAM-scanned
+    /// `sprite_scanline` into the per-pixel layer stack
+    pub fn draw_sprites(&mut self) {
         for x in 0..(PIXELS_PER_LINE as usize) {
-            if (self.sprite_scanline[x] & (1 << 31)) != 0 {
-                let color16 = (self.sprite_scanline[x] & 0xFFFF) as u16;
-                let color = 0xFF000000u32
-                    | ((((color16 & 0x1F) << 3) as u32) << 16)
-                    | ((((color16 >> 5) & 0x1F) << 3) as u32) << 8
-                    | ((((color16 >> 10) & 0x1F) << 3) as u32);
-                self.framebuffer[x + line] = color;
+            let entry = self.sprite_scanline[x];
+            if (entry & (1 << 31)) != 0 && (self.window_mask[x] & (1 << 4)) != 0 {
+                let color16 = (entry & 0xFFFF) as u16;
+                let priority = ((entry >> 16) & 0x3) as u8;
+                let semi_transparent = (entry & (1 << 18)) != 0;
+                self.insert_layer(x, color16, LAYER_OBJ, priority, semi_transparent);
             }
         }
     }
 
-    /// Draw rot/scale sprite (partial)
-    pub fn draw_rotscale_sprite(&mut self, _attributes: &[u16]) {
-        // Complex; left as partial stub for now
+    /// Decide whether the upcoming frame should be skipped, based on the
+    /// current frameskip policy and whether a display capture is in flight.
+    /// Should be called once per frame by the emulator's frame loop, before
+    /// any scanlines are drawn.
+    pub fn decide_frameskip(&mut self) {
+        self.frameskip.allow = !self.disp_capcnt.enable_busy;
+
+        if !self.frameskip.allow {
+            self.frameskip.active = false;
+            self.frameskip.cnt = 0;
+            self.frameskip.frame_ready = true;
+            return;
+        }
+
+        match self.frameskip.set {
+            0 => {
+                self.frameskip.active = false;
+                self.frameskip.cnt = 0;
+                self.frameskip.frame_ready = true;
+            }
+            n if n > 0 => {
+                if self.frameskip.cnt < n {
+                    self.frameskip.active = true;
+                    self.frameskip.cnt += 1;
+                    self.frameskip.frame_ready = false;
+                } else {
+                    self.frameskip.active = false;
+                    self.frameskip.cnt = 0;
+                    self.frameskip.frame_ready = true;
+                }
+            }
+            _ => {
+                // Auto (-1): driven by external advice from the frontend.
+                if self.frameskip.advice {
+                    self.frameskip.active = true;
+                    self.frameskip.cnt += 1;
+                    self.frameskip.frame_ready = false;
+                } else {
+                    self.frameskip.active = false;
+                    self.frameskip.cnt = 0;
+                    self.frameskip.frame_ready = true;
+                }
+            }
+        }
+    }
+
+    /// Set the frameskip policy (-1 = auto, 0 = off, N = skip N frames per render)
+    pub fn set_frameskip(&mut self, set: i32) {
+        self.frameskip.set = set;
+        self.frameskip.cnt = 0;
+    }
+
+    /// Give the auto-frameskip heuristic feedback that the host is falling behind
+    pub fn set_frameskip_advice(&mut self, advice: bool) {
+        self.frameskip.advice = advice;
+    }
+
+    /// Whether the frame currently in progress is being skipped
+    pub fn is_frame_skipped(&self) -> bool {
+        self.frameskip.active
+    }
+
+    /// Whether the last-decided frame was actually rendered (not skipped)
+    pub fn is_frame_ready(&self) -> bool {
+        self.frameskip.frame_ready
     }
 
     /// Draw one scanline composing backgrounds, sprites, windows, blending
     pub fn draw_scanline(&mut self) {
+        if self.frameskip.active {
+            // Skipped frame: still let the capture unit progress so
+            // feedback/motion-blur effects stay in sync, but skip all
+            // pixel compositing work.
+            self.run_display_capture();
+            return;
+        }
+
         // Initialize line
         let vcount = if let Some(g) = &self.gpu {
             g.lock().unwrap().get_VCOUNT()
@@ -294,8 +1317,18 @@ impl Gpu2DEngine {
             self.final_bg_priority[i] = 0xFF;
         }
 
+        for i in 0..(PIXELS_PER_LINE as usize) {
+            self.layer_stacks[i] = LayerStack::default();
+        }
+
         self.draw_backdrop();
 
+        // The OAM scan must run before the window mask so OBJ-window
+        // coverage (mode 2 sprites) is available to it.
+        if self.disp_cnt.display_obj {
+            self.scan_oam();
+        }
+
         // Window mask
         if self.disp_cnt.display_win0 || self.disp_cnt.display_win1 || self.disp_cnt.obj_win_display
         {
@@ -345,7 +1378,11 @@ impl Gpu2DEngine {
             self.draw_sprites();
         }
 
-        // blending/effects omitted (TODO)
+        self.apply_color_effects();
+
+        self.run_display_capture();
+
+        self.apply_master_bright();
 
         // Compose front framebuffer according to display_mode
         match self.disp_cnt.display_mode {
@@ -402,6 +1439,54 @@ impl Gpu2DEngine {
         self.bg3y_internal = self.bg3y as i32;
 
         self.disp_capcnt_val = 0;
+
+        self.tap_display_frame();
+    }
+
+    /// Register a sink to receive a copy of every completed frame (pass
+    /// `None` to stop recording). Both the on-screen composited output and
+    /// the raw DISPCAPCNT capture output are routed to it, tagged by
+    /// [`FrameTag`]/[`FrameKind`] so a recorder can tell them apart.
+    pub fn set_frame_sink(&mut self, sink: Option<Box<dyn FrameSink>>) {
+        self.frame_sink = sink;
+    }
+
+    /// Hand the just-finished on-screen frame to the registered [`FrameSink`]
+    fn tap_display_frame(&mut self) {
+        if self.frame_sink.is_none() {
+            return;
+        }
+        let width = PIXELS_PER_LINE as u32;
+        let height = SCANLINES as u32;
+        let mut bytes = Vec::with_capacity(self.front_framebuffer.len() * 4);
+        for &argb in &self.front_framebuffer {
+            let r = ((argb >> 16) & 0xFF) as u8;
+            let g = ((argb >> 8) & 0xFF) as u8;
+            let b = (argb & 0xFF) as u8;
+            bytes.extend_from_slice(&[r, g, b, 0xFF]);
+        }
+        let tag = if self.engine_a { FrameTag::EngineA } else { FrameTag::EngineB };
+        self.frame_sink
+            .as_mut()
+            .unwrap()
+            .on_frame(&bytes, width, height, tag, FrameKind::Display);
+    }
+
+    /// Hand the just-finished DISPCAPCNT capture to the registered
+    /// [`FrameSink`], so recorders can capture exactly what the hardware
+    /// capture unit saw rather than only the final on-screen image
+    fn tap_capture_frame(&mut self, width: u32, height: u32) {
+        if self.frame_sink.is_none() {
+            return;
+        }
+        let mut bytes = Vec::with_capacity(self.capture_buffer.len() * 4);
+        for &color16 in &self.capture_buffer {
+            bytes.extend_from_slice(&Self::color15_to_rgba_bytes(color16));
+        }
+        self.frame_sink
+            .as_mut()
+            .unwrap()
+            .on_frame(&bytes, width, height, FrameTag::EngineA, FrameKind::Capture);
     }
 
     /// Registers getters
@@ -668,31 +1753,354 @@ impl Gpu2DEngine {
         self.master_bright = halfword;
     }
     pub fn set_disp_capcnt(&mut self, word: u32) {
+        use dispcapcnt_bits::*;
+
         if !self.engine_a {
             return;
         }
-        self.disp_capcnt.eva = (word & 0x1F) as i32;
-        if self.disp_capcnt.eva > 16 {
-            self.disp_capcnt.eva = 16;
-        }
-        self.disp_capcnt.evb = ((word >> 8) & 0x1F) as i32;
-        if self.disp_capcnt.evb > 16 {
-            self.disp_capcnt.evb = 16;
-        }
-        self.disp_capcnt.vram_write_block = ((word >> 16) & 0x3) as i32;
-        self.disp_capcnt.vram_write_offset = ((word >> 18) & 0x3) as i32;
-        self.disp_capcnt.capture_size = ((word >> 20) & 0x3) as i32;
-        self.disp_capcnt.a_3d_only = (word & (1 << 24)) != 0;
-        self.disp_capcnt.b_display_fifo = (word & (1 << 25)) != 0;
-        self.disp_capcnt.vram_read_offset = ((word >> 26) & 0x3) as i32;
-        self.disp_capcnt.capture_source = ((word >> 29) & 0x3) as i32;
-        if !self.disp_capcnt.enable_busy && (word & (1 << 31)) != 0 {
+
+        self.disp_capcnt.eva = EVA.get(word).min(16) as i32;
+        self.disp_capcnt.evb = EVB.get(word).min(16) as i32;
+        self.disp_capcnt.vram_write_block = VRAM_WRITE_BLOCK.get(word) as i32;
+        self.disp_capcnt.vram_write_offset = VRAM_WRITE_OFFSET.get(word) as i32;
+        self.disp_capcnt.capture_size = CAPTURE_SIZE.get(word) as i32;
+        self.disp_capcnt.a_3d_only = SRC_A_3D_ONLY.get(word) != 0;
+        self.disp_capcnt.b_display_fifo = SRC_B_DISPLAY_FIFO.get(word) != 0;
+        self.disp_capcnt.vram_read_offset = VRAM_READ_OFFSET.get(word) as i32;
+        self.disp_capcnt.capture_source = CAPTURE_SOURCE.get(word) as i32;
+
+        let enable_busy = ENABLE_BUSY.get(word) != 0;
+        if !self.disp_capcnt.enable_busy && enable_busy {
             self.captured_lines = -1;
         }
-        self.disp_capcnt.enable_busy = (word & (1 << 31)) != 0;
+        self.disp_capcnt.enable_busy = enable_busy;
+
         // reflect raw reg too
         self.disp_capcnt_val = word;
     }
+
+    /// Write a single byte into DISPCAPCNT at byte `offset` (0..=3), the way
+    /// the DS bus allows byte-wise IO writes, then re-derive every decoded
+    /// field from the updated raw value
+    pub fn set_disp_capcnt_byte(&mut self, offset: usize, byte: u8) {
+        if offset > 3 {
+            return;
+        }
+        let shift = (offset as u32) * 8;
+        let word = (self.disp_capcnt_val & !(0xFFu32 << shift)) | ((byte as u32) << shift);
+        self.set_disp_capcnt(word);
+    }
+
+    /// Write the low or high halfword of DISPCAPCNT (`offset` 0 or 2), then
+    /// re-derive every decoded field from the updated raw value
+    pub fn set_disp_capcnt_halfword(&mut self, offset: usize, halfword: u16) {
+        if offset != 0 && offset != 2 {
+            return;
+        }
+        let shift = (offset as u32) * 8;
+        let word = (self.disp_capcnt_val & !(0xFFFFu32 << shift)) | ((halfword as u32) << shift);
+        self.set_disp_capcnt(word);
+    }
+
+    /// Decode the DISPCAPCNT fields (plus `captured_lines`) into `(name, value)`
+    /// pairs for a debugger/inspector UI, without requiring callers to re-parse
+    /// raw bits; DISPCNT, BLDCNT, BLDALPHA, BLDY and MASTER_BRIGHT are listed
+    /// as raw values only
+    pub fn dump_registers(&self) -> Vec<(&'static str, u32)> {
+        vec![
+            ("DISPCNT", self.get_disp_cnt()),
+            ("DISPCAPCNT", self.disp_capcnt_val),
+            ("DISPCAPCNT.eva", self.disp_capcnt.eva as u32),
+            ("DISPCAPCNT.evb", self.disp_capcnt.evb as u32),
+            ("DISPCAPCNT.vram_write_block", self.disp_capcnt.vram_write_block as u32),
+            ("DISPCAPCNT.vram_write_offset", self.disp_capcnt.vram_write_offset as u32),
+            ("DISPCAPCNT.capture_size", self.disp_capcnt.capture_size as u32),
+            ("DISPCAPCNT.capture_source", self.disp_capcnt.capture_source as u32),
+            ("DISPCAPCNT.a_3d_only", self.disp_capcnt.a_3d_only as u32),
+            ("DISPCAPCNT.b_display_fifo", self.disp_capcnt.b_display_fifo as u32),
+            ("DISPCAPCNT.vram_read_offset", self.disp_capcnt.vram_read_offset as u32),
+            ("DISPCAPCNT.enable_busy", self.disp_capcnt.enable_busy as u32),
+            ("DISPCAPCNT.captured_lines", self.captured_lines as u32),
+            ("BLDCNT", self.get_bldcnt() as u32),
+            ("BLDALPHA", self.bldalpha as u32),
+            ("BLDY", self.bldy as u32),
+            ("MASTER_BRIGHT", self.master_bright as u32),
+        ]
+    }
+
+    /// Magic tag identifying a frozen `Gpu2DEngine` state block
+    const FREEZE_MAGIC: u32 = 0x4732_4446; // "G2DF"
+    /// Bump on any layout change so stale states are rejected, not mis-loaded
+    const FREEZE_VERSION: u32 = 1;
+
+    /// Serialize the engine's complete register state - including the
+    /// display-capture unit's in-flight state (`disp_capcnt_val`, the decoded
+    /// `disp_capcnt` fields, and `captured_lines`) - into a versioned byte
+    /// block, mirroring the `GPUfreeze` concept from PSX plugins. The
+    /// palette/VRAM backing store is owned by the shared [`crate::gpu::Gpu`]
+    /// and is frozen separately by that owner.
+    pub fn freeze(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        push_u32(&mut buf, Self::FREEZE_MAGIC);
+        push_u32(&mut buf, Self::FREEZE_VERSION);
+
+        push_bool(&mut buf, self.engine_a);
+
+        let d = &self.disp_cnt;
+        push_i32(&mut buf, d.bg_mode);
+        push_bool(&mut buf, d.bg_3d);
+        push_bool(&mut buf, d.tile_obj_1d);
+        push_bool(&mut buf, d.bitmap_obj_square);
+        push_bool(&mut buf, d.bitmap_obj_1d);
+        push_bool(&mut buf, d.display_bg0);
+        push_bool(&mut buf, d.display_bg1);
+        push_bool(&mut buf, d.display_bg2);
+        push_bool(&mut buf, d.display_bg3);
+        push_bool(&mut buf, d.display_obj);
+        push_bool(&mut buf, d.display_win0);
+        push_bool(&mut buf, d.display_win1);
+        push_bool(&mut buf, d.obj_win_display);
+        push_i32(&mut buf, d.display_mode);
+        push_i32(&mut buf, d.vram_block);
+        push_i32(&mut buf, d.tile_obj_1d_bound);
+        push_bool(&mut buf, d.bitmap_obj_1d_bound);
+        push_bool(&mut buf, d.hblank_obj_processing);
+        push_i32(&mut buf, d.char_base);
+        push_i32(&mut buf, d.screen_base);
+        push_bool(&mut buf, d.bg_extended_palette);
+        push_bool(&mut buf, d.obj_extended_palette);
+
+        let c = &self.disp_capcnt;
+        push_i32(&mut buf, c.eva);
+        push_i32(&mut buf, c.evb);
+        push_i32(&mut buf, c.vram_write_block);
+        push_i32(&mut buf, c.vram_write_offset);
+        push_i32(&mut buf, c.capture_size);
+        push_bool(&mut buf, c.a_3d_only);
+        push_bool(&mut buf, c.b_display_fifo);
+        push_i32(&mut buf, c.vram_read_offset);
+        push_i32(&mut buf, c.capture_source);
+        push_bool(&mut buf, c.enable_busy);
+        push_u32(&mut buf, self.disp_capcnt_val);
+        push_i32(&mut buf, self.captured_lines);
+
+        for v in self.bgcnt {
+            push_u16(&mut buf, v);
+        }
+        for v in self.bghofs {
+            push_u16(&mut buf, v);
+        }
+        for v in self.bgvofs {
+            push_u16(&mut buf, v);
+        }
+        for v in self.bg2p {
+            push_u16(&mut buf, v);
+        }
+        for v in self.bg3p {
+            push_u16(&mut buf, v);
+        }
+        push_u32(&mut buf, self.bg2x);
+        push_u32(&mut buf, self.bg2y);
+        push_u32(&mut buf, self.bg3x);
+        push_u32(&mut buf, self.bg3y);
+        for v in self.bg2p_internal {
+            push_i16(&mut buf, v);
+        }
+        for v in self.bg3p_internal {
+            push_i16(&mut buf, v);
+        }
+        push_i32(&mut buf, self.bg2x_internal);
+        push_i32(&mut buf, self.bg2y_internal);
+        push_i32(&mut buf, self.bg3x_internal);
+        push_i32(&mut buf, self.bg3y_internal);
+
+        push_u16(&mut buf, self.win0h);
+        push_u16(&mut buf, self.win1h);
+        push_u16(&mut buf, self.win0v);
+        push_u16(&mut buf, self.win1v);
+        push_u16(&mut buf, self.mosaic);
+
+        for v in self.winin.win0_bg_enabled {
+            push_bool(&mut buf, v);
+        }
+        push_bool(&mut buf, self.winin.win0_obj_enabled);
+        push_bool(&mut buf, self.winin.win0_color_special);
+        for v in self.winin.win1_bg_enabled {
+            push_bool(&mut buf, v);
+        }
+        push_bool(&mut buf, self.winin.win1_obj_enabled);
+        push_bool(&mut buf, self.winin.win1_color_special);
+
+        for v in self.winout.outside_bg_enabled {
+            push_bool(&mut buf, v);
+        }
+        push_bool(&mut buf, self.winout.outside_obj_enabled);
+        push_bool(&mut buf, self.winout.outside_color_special);
+        for v in self.winout.objwin_bg_enabled {
+            push_bool(&mut buf, v);
+        }
+        push_bool(&mut buf, self.winout.objwin_obj_enabled);
+        push_bool(&mut buf, self.winout.objwin_color_special);
+
+        push_bool(&mut buf, self.win0_active);
+        push_bool(&mut buf, self.win1_active);
+
+        for v in self.bldcnt.bg_first_target_pix {
+            push_bool(&mut buf, v);
+        }
+        push_bool(&mut buf, self.bldcnt.obj_first_target_pix);
+        push_bool(&mut buf, self.bldcnt.bd_first_target_pix);
+        push_i32(&mut buf, self.bldcnt.effect);
+        for v in self.bldcnt.bg_second_target_pix {
+            push_bool(&mut buf, v);
+        }
+        push_bool(&mut buf, self.bldcnt.obj_second_target_pix);
+        push_bool(&mut buf, self.bldcnt.bd_second_target_pix);
+
+        push_u16(&mut buf, self.bldalpha);
+        push_u8(&mut buf, self.bldy);
+        push_u16(&mut buf, self.master_bright);
+
+        buf
+    }
+
+    /// Restore register state previously produced by [`Self::freeze`].
+    /// Rejects blocks with a missing/wrong magic tag or an unsupported
+    /// version rather than risk silently mis-loading a stale layout.
+    pub fn unfreeze(&mut self, data: &[u8]) -> Result<(), String> {
+        let mut r = FreezeReader::new(data);
+
+        let magic = r.read_u32()?;
+        if magic != Self::FREEZE_MAGIC {
+            return Err("Gpu2DEngine freeze: bad magic tag".to_string());
+        }
+        let version = r.read_u32()?;
+        if version != Self::FREEZE_VERSION {
+            return Err(format!(
+                "Gpu2DEngine freeze: unsupported version {version} (expected {})",
+                Self::FREEZE_VERSION
+            ));
+        }
+
+        self.engine_a = r.read_bool()?;
+
+        let d = &mut self.disp_cnt;
+        d.bg_mode = r.read_i32()?;
+        d.bg_3d = r.read_bool()?;
+        d.tile_obj_1d = r.read_bool()?;
+        d.bitmap_obj_square = r.read_bool()?;
+        d.bitmap_obj_1d = r.read_bool()?;
+        d.display_bg0 = r.read_bool()?;
+        d.display_bg1 = r.read_bool()?;
+        d.display_bg2 = r.read_bool()?;
+        d.display_bg3 = r.read_bool()?;
+        d.display_obj = r.read_bool()?;
+        d.display_win0 = r.read_bool()?;
+        d.display_win1 = r.read_bool()?;
+        d.obj_win_display = r.read_bool()?;
+        d.display_mode = r.read_i32()?;
+        d.vram_block = r.read_i32()?;
+        d.tile_obj_1d_bound = r.read_i32()?;
+        d.bitmap_obj_1d_bound = r.read_bool()?;
+        d.hblank_obj_processing = r.read_bool()?;
+        d.char_base = r.read_i32()?;
+        d.screen_base = r.read_i32()?;
+        d.bg_extended_palette = r.read_bool()?;
+        d.obj_extended_palette = r.read_bool()?;
+
+        let c = &mut self.disp_capcnt;
+        c.eva = r.read_i32()?;
+        c.evb = r.read_i32()?;
+        c.vram_write_block = r.read_i32()?;
+        c.vram_write_offset = r.read_i32()?;
+        c.capture_size = r.read_i32()?;
+        c.a_3d_only = r.read_bool()?;
+        c.b_display_fifo = r.read_bool()?;
+        c.vram_read_offset = r.read_i32()?;
+        c.capture_source = r.read_i32()?;
+        c.enable_busy = r.read_bool()?;
+        self.disp_capcnt_val = r.read_u32()?;
+        self.captured_lines = r.read_i32()?;
+
+        for v in self.bgcnt.iter_mut() {
+            *v = r.read_u16()?;
+        }
+        for v in self.bghofs.iter_mut() {
+            *v = r.read_u16()?;
+        }
+        for v in self.bgvofs.iter_mut() {
+            *v = r.read_u16()?;
+        }
+        for v in self.bg2p.iter_mut() {
+            *v = r.read_u16()?;
+        }
+        for v in self.bg3p.iter_mut() {
+            *v = r.read_u16()?;
+        }
+        self.bg2x = r.read_u32()?;
+        self.bg2y = r.read_u32()?;
+        self.bg3x = r.read_u32()?;
+        self.bg3y = r.read_u32()?;
+        for v in self.bg2p_internal.iter_mut() {
+            *v = r.read_i16()?;
+        }
+        for v in self.bg3p_internal.iter_mut() {
+            *v = r.read_i16()?;
+        }
+        self.bg2x_internal = r.read_i32()?;
+        self.bg2y_internal = r.read_i32()?;
+        self.bg3x_internal = r.read_i32()?;
+        self.bg3y_internal = r.read_i32()?;
+
+        self.win0h = r.read_u16()?;
+        self.win1h = r.read_u16()?;
+        self.win0v = r.read_u16()?;
+        self.win1v = r.read_u16()?;
+        self.mosaic = r.read_u16()?;
+
+        for v in self.winin.win0_bg_enabled.iter_mut() {
+            *v = r.read_bool()?;
+        }
+        self.winin.win0_obj_enabled = r.read_bool()?;
+        self.winin.win0_color_special = r.read_bool()?;
+        for v in self.winin.win1_bg_enabled.iter_mut() {
+            *v = r.read_bool()?;
+        }
+        self.winin.win1_obj_enabled = r.read_bool()?;
+        self.winin.win1_color_special = r.read_bool()?;
+
+        for v in self.winout.outside_bg_enabled.iter_mut() {
+            *v = r.read_bool()?;
+        }
+        self.winout.outside_obj_enabled = r.read_bool()?;
+        self.winout.outside_color_special = r.read_bool()?;
+        for v in self.winout.objwin_bg_enabled.iter_mut() {
+            *v = r.read_bool()?;
+        }
+        self.winout.objwin_obj_enabled = r.read_bool()?;
+        self.winout.objwin_color_special = r.read_bool()?;
+
+        self.win0_active = r.read_bool()?;
+        self.win1_active = r.read_bool()?;
+
+        for v in self.bldcnt.bg_first_target_pix.iter_mut() {
+            *v = r.read_bool()?;
+        }
+        self.bldcnt.obj_first_target_pix = r.read_bool()?;
+        self.bldcnt.bd_first_target_pix = r.read_bool()?;
+        self.bldcnt.effect = r.read_i32()?;
+        for v in self.bldcnt.bg_second_target_pix.iter_mut() {
+            *v = r.read_bool()?;
+        }
+        self.bldcnt.obj_second_target_pix = r.read_bool()?;
+        self.bldcnt.bd_second_target_pix = r.read_bool()?;
+
+        self.bldalpha = r.read_u16()?;
+        self.bldy = r.read_u8()?;
+        self.master_bright = r.read_u16()?;
+
+        Ok(())
+    }
 }
 
 impl Default for Gpu2DEngine {
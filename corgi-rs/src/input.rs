@@ -0,0 +1,221 @@
+/// Input subsystem: resolves keyboard scancodes and gamepad events to
+/// actions via the remappable table in [`crate::config::Config`], and tracks
+/// the held-button state in the bit layout the DS KEYINPUT register expects
+/// so [`crate::corgi_core::CorgiCore`] can consume it directly.
+///
+/// Gamepad polling is built on `gilrs` and only compiled under the
+/// `gamepad` Cargo feature, mirroring how `renderer3d.rs` gates its
+/// `wgpu-renderer` backend.
+use crate::config::Config;
+use crate::corgi_core::DSKey;
+
+/// Everything a bound input can trigger: one of the 12 DS buttons, or a
+/// frontend-level toggle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputAction {
+    Key(DSKey),
+    ToggleFramelimiter,
+    ToggleFrameskip,
+    Pause,
+    Screenshot,
+}
+
+impl InputAction {
+    /// Serialize to the token `Config::save` writes to disk
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InputAction::Key(DSKey::Up) => "Up",
+            InputAction::Key(DSKey::Down) => "Down",
+            InputAction::Key(DSKey::Left) => "Left",
+            InputAction::Key(DSKey::Right) => "Right",
+            InputAction::Key(DSKey::A) => "A",
+            InputAction::Key(DSKey::B) => "B",
+            InputAction::Key(DSKey::X) => "X",
+            InputAction::Key(DSKey::Y) => "Y",
+            InputAction::Key(DSKey::L) => "L",
+            InputAction::Key(DSKey::R) => "R",
+            InputAction::Key(DSKey::Start) => "Start",
+            InputAction::Key(DSKey::Select) => "Select",
+            InputAction::Key(DSKey::Debugging) => "Debugging",
+            InputAction::ToggleFramelimiter => "ToggleFramelimiter",
+            InputAction::ToggleFrameskip => "ToggleFrameskip",
+            InputAction::Pause => "Pause",
+            InputAction::Screenshot => "Screenshot",
+        }
+    }
+
+    /// Parse the token written by [`InputAction::as_str`]
+    pub fn from_str(s: &str) -> Option<InputAction> {
+        Some(match s {
+            "Up" => InputAction::Key(DSKey::Up),
+            "Down" => InputAction::Key(DSKey::Down),
+            "Left" => InputAction::Key(DSKey::Left),
+            "Right" => InputAction::Key(DSKey::Right),
+            "A" => InputAction::Key(DSKey::A),
+            "B" => InputAction::Key(DSKey::B),
+            "X" => InputAction::Key(DSKey::X),
+            "Y" => InputAction::Key(DSKey::Y),
+            "L" => InputAction::Key(DSKey::L),
+            "R" => InputAction::Key(DSKey::R),
+            "Start" => InputAction::Key(DSKey::Start),
+            "Select" => InputAction::Key(DSKey::Select),
+            "Debugging" => InputAction::Key(DSKey::Debugging),
+            "ToggleFramelimiter" => InputAction::ToggleFramelimiter,
+            "ToggleFrameskip" => InputAction::ToggleFrameskip,
+            "Pause" => InputAction::Pause,
+            "Screenshot" => InputAction::Screenshot,
+            _ => return None,
+        })
+    }
+}
+
+/// Bit positions of the DS KEYINPUT register (`0x4000130`), active-low on
+/// real hardware: a set bit means "released"
+const KEYINPUT_BIT: [DSKey; 10] = [
+    DSKey::A,
+    DSKey::B,
+    DSKey::Select,
+    DSKey::Start,
+    DSKey::Right,
+    DSKey::Left,
+    DSKey::Up,
+    DSKey::Down,
+    DSKey::R,
+    DSKey::L,
+];
+
+/// Tracks which DS buttons are currently held, independent of which input
+/// device (keyboard or gamepad) pressed them
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeyState {
+    held: u16,
+}
+
+impl KeyState {
+    pub fn new() -> Self {
+        KeyState { held: 0 }
+    }
+
+    fn bit_for(key: DSKey) -> Option<u32> {
+        KEYINPUT_BIT.iter().position(|&k| k == key).map(|i| i as u32)
+    }
+
+    pub fn set(&mut self, key: DSKey, pressed: bool) {
+        let Some(bit) = Self::bit_for(key) else {
+            return;
+        };
+        if pressed {
+            self.held |= 1 << bit;
+        } else {
+            self.held &= !(1 << bit);
+        }
+    }
+
+    /// The held-button mask in real KEYINPUT polarity: held buttons read 0
+    pub fn keyinput_bits(&self) -> u16 {
+        !self.held & 0x3FF
+    }
+}
+
+/// Frontend-agnostic input source: resolves raw keyboard/gamepad events to
+/// actions using the bindings in [`Config`] and keeps the DS button state
+pub struct InputManager {
+    keys: KeyState,
+    #[cfg(feature = "gamepad")]
+    gilrs: Option<gilrs::Gilrs>,
+}
+
+impl InputManager {
+    pub fn new() -> Self {
+        InputManager {
+            keys: KeyState::new(),
+            #[cfg(feature = "gamepad")]
+            gilrs: gilrs::Gilrs::new().ok(),
+        }
+    }
+
+    /// Resolve a keyboard scancode press/release into an action, if bound
+    pub fn handle_key_code(&mut self, code: u32, pressed: bool) -> Option<InputAction> {
+        let action = *Config::lock().key_bindings.get(&code)?;
+        if let InputAction::Key(key) = action {
+            self.keys.set(key, pressed);
+        }
+        Some(action)
+    }
+
+    /// Directly set a DS button, bypassing the binding table (used by
+    /// on-screen touch controls)
+    pub fn set_key(&mut self, key: DSKey, pressed: bool) {
+        self.keys.set(key, pressed);
+    }
+
+    /// The held-button mask in DS KEYINPUT polarity
+    pub fn keyinput_bits(&self) -> u16 {
+        self.keys.keyinput_bits()
+    }
+
+    /// Poll the first connected gamepad and resolve any new button/axis
+    /// events into actions, applying the configured deadzone to the D-pad
+    /// stand-in axes
+    #[cfg(feature = "gamepad")]
+    pub fn poll_gamepad(&mut self) -> Vec<InputAction> {
+        let mut actions = Vec::new();
+        let Some(gilrs) = self.gilrs.as_mut() else {
+            return actions;
+        };
+
+        while let Some(gilrs::Event { event, .. }) = gilrs.next_event() {
+            match event {
+                gilrs::EventType::ButtonPressed(button, _) => {
+                    let code = button as u32;
+                    if let Some(&action) = Config::lock().gamepad_bindings.get(&code) {
+                        if let InputAction::Key(key) = action {
+                            self.keys.set(key, true);
+                        }
+                        actions.push(action);
+                    }
+                }
+                gilrs::EventType::ButtonReleased(button, _) => {
+                    let code = button as u32;
+                    if let Some(&action) = Config::lock().gamepad_bindings.get(&code) {
+                        if let InputAction::Key(key) = action {
+                            self.keys.set(key, false);
+                        }
+                        actions.push(action);
+                    }
+                }
+                gilrs::EventType::AxisChanged(axis, value, _) => {
+                    let deadzone = Config::lock().gamepad_deadzone;
+                    let (neg, pos) = match axis {
+                        gilrs::Axis::LeftStickX => (DSKey::Left, DSKey::Right),
+                        gilrs::Axis::LeftStickY => (DSKey::Down, DSKey::Up),
+                        _ => continue,
+                    };
+                    if value.abs() < deadzone {
+                        // Back inside the deadzone: release whichever
+                        // direction was held, rather than leaving it stuck.
+                        self.keys.set(neg, false);
+                        self.keys.set(pos, false);
+                    } else {
+                        self.keys.set(neg, value < -deadzone);
+                        self.keys.set(pos, value > deadzone);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        actions
+    }
+
+    #[cfg(not(feature = "gamepad"))]
+    pub fn poll_gamepad(&mut self) -> Vec<InputAction> {
+        Vec::new()
+    }
+}
+
+impl Default for InputManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,5 +1,11 @@
-/// Interrupt definitions and registers for the emulator
-/// Provides IRQ constants and helper for checking pending requests
+/// Interrupt definitions and dispatch for the emulator
+///
+/// `InterruptRegs` is a single CPU's IE/IF/IME bank; `InterruptController`
+/// holds one bank per core and is the active IRQ controller the rest of the
+/// emulator drives: the GPU's `check_fifo_irq`, the timers, VBlank/HBlank/
+/// VCountMatch, and the IPC FIFO all call `request_interrupt` (or the
+/// ARM9/ARM7-specific variants, for sources only one core observes) instead
+/// of poking IF bits directly.
 
 /// Interrupt sources used by the system
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -59,7 +65,16 @@ impl From<Interrupt> for u32 {
     }
 }
 
-/// Interrupt register block
+/// Which CPU core's register bank to address. The DS has two independent
+/// IE/IF/IME sets, one per CPU, since ARM9 and ARM7 run their own interrupt
+/// handlers and mask sources differently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CpuId {
+    Arm9,
+    Arm7,
+}
+
+/// One CPU's interrupt register block
 #[derive(Debug, Clone, Copy)]
 pub struct InterruptRegs {
     /// Interrupt Master Enable (IME)
@@ -76,6 +91,24 @@ impl InterruptRegs {
     pub fn is_requesting_int(&self, bit_mask: u32) -> bool {
         (self.ie & bit_mask) != 0 && (self.iflags & bit_mask) != 0
     }
+
+    /// Whether this bank has any interrupt that should break the CPU out of
+    /// halt and vector to the IRQ handler
+    pub fn pending(&self) -> bool {
+        self.ime != 0 && (self.ie & self.iflags) != 0
+    }
+
+    /// OR `bit_mask` into IF, as hardware does when a peripheral raises an
+    /// interrupt
+    fn request(&mut self, bit_mask: u32) {
+        self.iflags |= bit_mask;
+    }
+
+    /// Clear the IF bits named in `bit_mask`; on real hardware, IF is
+    /// acknowledged by writing 1 to the bits you want cleared
+    pub fn acknowledge(&mut self, bit_mask: u32) {
+        self.iflags &= !bit_mask;
+    }
 }
 
 impl Default for InterruptRegs {
@@ -83,3 +116,60 @@ impl Default for InterruptRegs {
         InterruptRegs { ime: 0, ie: 0, iflags: 0 }
     }
 }
+
+/// Active IRQ controller: owns both CPUs' register banks and is the single
+/// place that raises and acknowledges interrupts
+#[derive(Debug, Clone, Copy, Default)]
+pub struct InterruptController {
+    pub arm9: InterruptRegs,
+    pub arm7: InterruptRegs,
+}
+
+impl InterruptController {
+    pub fn new() -> Self {
+        InterruptController::default()
+    }
+
+    /// Raise an interrupt that both cores observe (VBlank/HBlank/VCountMatch,
+    /// the timers, IPC sync, and most other sources fall into this category
+    /// on real hardware, since the physical event is CPU-agnostic and each
+    /// core's own IE decides whether it actually fires)
+    pub fn request_interrupt(&mut self, interrupt: Interrupt) {
+        let bit = 1u32 << u32::from(interrupt);
+        self.arm9.request(bit);
+        self.arm7.request(bit);
+    }
+
+    /// Raise an interrupt visible to only one core (e.g. `GeometryFifo`,
+    /// which the GPU only wires to ARM9)
+    pub fn request_interrupt_on(&mut self, cpu: CpuId, interrupt: Interrupt) {
+        let bit = 1u32 << u32::from(interrupt);
+        self.bank_mut(cpu).request(bit);
+    }
+
+    /// Acknowledge (clear) IF bits on one core's bank, as happens when that
+    /// core writes to its IF register
+    pub fn acknowledge(&mut self, cpu: CpuId, bit_mask: u32) {
+        self.bank_mut(cpu).acknowledge(bit_mask);
+    }
+
+    /// Whether `cpu` has an enabled, flagged interrupt pending and should
+    /// break out of halt / vector to its IRQ handler
+    pub fn pending(&self, cpu: CpuId) -> bool {
+        self.bank(cpu).pending()
+    }
+
+    pub fn bank(&self, cpu: CpuId) -> &InterruptRegs {
+        match cpu {
+            CpuId::Arm9 => &self.arm9,
+            CpuId::Arm7 => &self.arm7,
+        }
+    }
+
+    pub fn bank_mut(&mut self, cpu: CpuId) -> &mut InterruptRegs {
+        match cpu {
+            CpuId::Arm9 => &mut self.arm9,
+            CpuId::Arm7 => &mut self.arm7,
+        }
+    }
+}
@@ -2,32 +2,40 @@
 /// Copyright PSISP 2017
 /// Licensed under the GPLv3
 /// See LICENSE.txt for details
-use druid::widget::{Container, Label};
-use druid::{AppLauncher, Color, Data, Lens, LocalizedString, UnitData, WidgetExt, WindowDesc};
+mod corgi_core;
 
+#[cfg(feature = "gui")]
 mod emu_window;
+
+#[cfg(feature = "gui")]
+use druid::widget::{Container, Label};
+#[cfg(feature = "gui")]
+use druid::{AppLauncher, Color, Data, Lens, LocalizedString, UnitData, WidgetExt, WindowDesc};
+#[cfg(feature = "gui")]
 use emu_window::EmuWindow;
 
-/// Application state
+/// Application state for the druid-backed `gui` frontend
+#[cfg(feature = "gui")]
 #[derive(Clone, Data)]
 struct AppState {
     /// Emulator window state
     window_initialized: bool,
 }
 
-/// Entry point for CorgiDS emulator
+/// Entry point for CorgiDS emulator, windowed frontend
+#[cfg(feature = "gui")]
 fn main() {
-    /// Initialize the main window descriptor
+    // Initialize the main window descriptor
     let main_window = WindowDesc::new(ui_builder())
         .title(LocalizedString::new("CorgiDS"))
         .window_size((800.0, 600.0));
 
-    /// Initial application state
+    // Initial application state
     let initial_state = AppState {
         window_initialized: false,
     };
 
-    /// Launch the application with Druid framework
+    // Launch the application with Druid framework
     AppLauncher::with_window(main_window)
         .log_to_console()
         .launch(initial_state)
@@ -35,20 +43,21 @@ fn main() {
 }
 
 /// Build the main UI
+#[cfg(feature = "gui")]
 fn ui_builder() -> impl druid::Widget<AppState> {
-    /// Create main emulator window
+    // Create main emulator window
     let emu_window = EmuWindow::new();
 
-    /// Initialize emulator
+    // Initialize emulator
     match emu_window.initialize() {
         Ok(_) => {
-            /// Successfully initialized - show the emulator window
+            // Successfully initialized - show the emulator window
             Container::new(Label::new("CorgiDS Emulator Running"))
                 .background(Color::rgb8(0x3d, 0x3d, 0x42))
                 .expand()
         }
         Err(e) => {
-            /// Failed to initialize - show error
+            // Failed to initialize - show error
             eprintln!("Failed to initialize emulator: {}", e);
             Container::new(Label::new(format!("Error: {}", e)))
                 .background(Color::rgb8(0xff, 0x00, 0x00))
@@ -56,3 +65,27 @@ fn ui_builder() -> impl druid::Widget<AppState> {
         }
     }
 }
+
+/// Headless entry point when the `gui` feature is disabled: drive the
+/// GUI-free `CorgiCore` directly, with no windowing toolkit involved
+#[cfg(not(feature = "gui"))]
+fn main() {
+    let mut core = corgi_core::CorgiCore::new();
+    if let Err(e) = core.init() {
+        eprintln!("Failed to initialize emulator: {}", e);
+        return;
+    }
+
+    match std::env::args().nth(1) {
+        Some(rom_path) => {
+            if let Err(e) = core.load_rom(&rom_path) {
+                eprintln!("Failed to load ROM: {}", e);
+            }
+        }
+        None => {
+            eprintln!(
+                "Usage: corgi-rs <rom-path> (build with --features gui for the windowed frontend)"
+            );
+        }
+    }
+}
@@ -0,0 +1,431 @@
+/// Pluggable rasterizer backend for the GX geometry engine
+///
+/// `Gpu3D` assembles polygons into `rend_poly`/`rend_vert` each frame and
+/// hands them to whichever `Renderer3D` is installed, keeping the
+/// command-processing front end independent of how pixels actually get
+/// rasterized. `SoftwareRenderer` is the always-available default; a
+/// `WgpuRenderer` is available behind the `wgpu-renderer` Cargo feature for
+/// GPU-accelerated rendering of the same polygon stream.
+use crate::gpu3d::{Disp3dCnt, Polygon, Vertex};
+use crate::memconsts::{PIXELS_PER_LINE, SCANLINES};
+
+/// A 3D rasterizer backend: clears its buffers, accepts polygons for the
+/// current frame, and presents the finished 256x192 frame as ARGB8888
+pub trait Renderer3D {
+    /// Begin a new frame, clearing the color/depth buffers
+    fn begin_frame(&mut self, clear_color: u32, clear_depth: u32);
+
+    /// Rasterize one polygon; `verts` holds exactly `poly.vertices` entries
+    fn submit_polygon(&mut self, poly: &Polygon, verts: &[Vertex], disp3dcnt: &Disp3dCnt);
+
+    /// Finish the frame (a no-op for the software path; submits the command
+    /// buffer and blocks on readback for GPU-backed renderers)
+    fn finish_frame(&mut self);
+
+    /// Borrow the finished frame as ARGB8888 pixels, `SCANLINES` rows of
+    /// `PIXELS_PER_LINE` each
+    fn framebuffer(&self) -> &[u32];
+}
+
+/// CPU scanline rasterizer; the default backend, always available
+pub struct SoftwareRenderer {
+    framebuffer: Vec<u32>,
+    depth_buffer: Vec<i64>,
+}
+
+impl SoftwareRenderer {
+    pub fn new() -> Self {
+        let pixels = (SCANLINES as usize) * (PIXELS_PER_LINE as usize);
+        SoftwareRenderer {
+            framebuffer: vec![0; pixels],
+            depth_buffer: vec![i64::MAX; pixels],
+        }
+    }
+
+    fn width(&self) -> usize {
+        PIXELS_PER_LINE as usize
+    }
+    fn height(&self) -> usize {
+        SCANLINES as usize
+    }
+
+    /// Project a clip-space vertex to screen-space `(x, y)` plus the `w`
+    /// used as the depth-test key
+    fn project(&self, v: &Vertex) -> (f64, f64, i64) {
+        let w = (v.coords[3] as f64).max(1.0);
+        let x = (v.coords[0] as f64 / w + 1.0) * 0.5 * self.width() as f64;
+        let y = (1.0 - v.coords[1] as f64 / w) * 0.5 * self.height() as f64;
+        (x, y, v.coords[3] as i64)
+    }
+
+    /// Fill one triangle via barycentric rasterization, Gouraud-shading the
+    /// vertex colors and depth-testing/alpha-blending each covered pixel
+    fn fill_triangle(
+        &mut self,
+        poly: &Polygon,
+        a: &Vertex,
+        b: &Vertex,
+        c: &Vertex,
+        disp3dcnt: &Disp3dCnt,
+    ) {
+        let (ax, ay, az) = self.project(a);
+        let (bx, by, bz) = self.project(b);
+        let (cx, cy, cz) = self.project(c);
+
+        let area = (bx - ax) * (cy - ay) - (cx - ax) * (by - ay);
+        if area == 0.0 {
+            return;
+        }
+
+        let min_x = ax.min(bx).min(cx).floor().max(0.0) as usize;
+        let max_x = (ax.max(bx).max(cx).ceil() as usize).min(self.width().saturating_sub(1));
+        let min_y = ay.min(by).min(cy).floor().max(0.0) as usize;
+        let max_y = (ay.max(by).max(cy).ceil() as usize).min(self.height().saturating_sub(1));
+
+        for py in min_y..=max_y {
+            for px in min_x..=max_x {
+                let fx = px as f64 + 0.5;
+                let fy = py as f64 + 0.5;
+
+                let w0 = ((bx - fx) * (cy - fy) - (cx - fx) * (by - fy)) / area;
+                let w1 = ((cx - fx) * (ay - fy) - (ax - fx) * (cy - fy)) / area;
+                let w2 = 1.0 - w0 - w1;
+                if w0 < 0.0 || w1 < 0.0 || w2 < 0.0 {
+                    continue;
+                }
+
+                let idx = py * self.width() + px;
+                let depth = (w0 * az as f64 + w1 * bz as f64 + w2 * cz as f64) as i64;
+                let passes_depth = if poly.attributes.depth_test_equal {
+                    (depth - self.depth_buffer[idx]).abs() <= (1 << 9)
+                } else {
+                    depth < self.depth_buffer[idx]
+                };
+                if !passes_depth {
+                    continue;
+                }
+
+                let lerp_ch =
+                    |i: usize| -> i32 { (w0 * a.colors[i] as f64 + w1 * b.colors[i] as f64 + w2 * c.colors[i] as f64) as i32 };
+                let src = color5_to_argb(lerp_ch(0), lerp_ch(1), lerp_ch(2), poly.attributes.alpha);
+
+                let out_color = if poly.translucent && disp3dcnt.alpha_blending {
+                    blend(self.framebuffer[idx], src, poly.attributes.alpha)
+                } else {
+                    src
+                };
+
+                self.framebuffer[idx] = out_color;
+                if !poly.translucent {
+                    self.depth_buffer[idx] = depth;
+                }
+            }
+        }
+    }
+}
+
+impl Default for SoftwareRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Renderer3D for SoftwareRenderer {
+    fn begin_frame(&mut self, clear_color: u32, clear_depth: u32) {
+        self.framebuffer.fill(clear_color);
+        self.depth_buffer.fill(clear_depth as i64);
+    }
+
+    fn submit_polygon(&mut self, poly: &Polygon, verts: &[Vertex], disp3dcnt: &Disp3dCnt) {
+        if verts.len() < 3 {
+            return;
+        }
+        // Every polygon `Gpu3D::add_polygon` produces is already a convex
+        // ring (3 or 4 vertices, more once clipping has cut corners off), so
+        // a simple fan triangulation suffices.
+        for i in 1..verts.len() - 1 {
+            self.fill_triangle(poly, &verts[0], &verts[i], &verts[i + 1], disp3dcnt);
+        }
+    }
+
+    fn finish_frame(&mut self) {}
+
+    fn framebuffer(&self) -> &[u32] {
+        &self.framebuffer
+    }
+}
+
+/// Convert a 5-bit-per-channel color plus a 5-bit alpha to ARGB8888, scaling
+/// each 0-31 channel up to 0-255
+fn color5_to_argb(r: i32, g: i32, b: i32, alpha5: i32) -> u32 {
+    let scale = |c: i32| -> u32 { (c.clamp(0, 31) as u32) * 255 / 31 };
+    (scale(alpha5) << 24) | (scale(r) << 16) | (scale(g) << 8) | scale(b)
+}
+
+/// Blend `src` over `dst` by `alpha5` (0-31) in ARGB8888
+fn blend(dst: u32, src: u32, alpha5: i32) -> u32 {
+    let a = (alpha5.clamp(0, 31) as u32) + 1; // 1..=32 so the `>> 5` rounds evenly
+    let mix = |s: u32, d: u32| -> u32 { (s * a + d * (32 - a)) >> 5 };
+    let (dr, dg, db) = ((dst >> 16) & 0xFF, (dst >> 8) & 0xFF, dst & 0xFF);
+    let (sr, sg, sb) = ((src >> 16) & 0xFF, (src >> 8) & 0xFF, src & 0xFF);
+    0xFF00_0000 | (mix(sr, dr) << 16) | (mix(sg, dg) << 8) | mix(sb, db)
+}
+
+/// GPU-accelerated backend built on `wgpu`. Disabled by default; enable the
+/// `wgpu-renderer` Cargo feature (mirroring how other Rust emulators expose
+/// an `opengl-renderer`/`wgpu-renderer` choice of backend) to render the same
+/// `rend_poly`/`rend_vert` stream on the GPU instead of the CPU.
+#[cfg(feature = "wgpu-renderer")]
+pub struct WgpuRenderer {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::RenderPipeline,
+    color_texture: wgpu::Texture,
+    depth_texture: wgpu::Texture,
+    /// `COPY_DST | MAP_READ` staging buffer `finish_frame` copies
+    /// `color_texture` into so it can be mapped back to the CPU; `256 *
+    /// size_of::<u32>()` is already a multiple of wgpu's 256-byte
+    /// `bytes_per_row` alignment, so no row padding is needed
+    staging_buffer: wgpu::Buffer,
+    vertices: Vec<GpuVertex>,
+    clear_color: u32,
+    clear_depth: u32,
+    readback: Vec<u32>,
+}
+
+#[cfg(feature = "wgpu-renderer")]
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct GpuVertex {
+    position: [f32; 4],
+    color: [f32; 4],
+}
+
+#[cfg(feature = "wgpu-renderer")]
+impl WgpuRenderer {
+    /// Build a renderer against an existing wgpu device/queue, shared with
+    /// whatever windowing/present surface the frontend owns
+    pub fn new(device: wgpu::Device, queue: wgpu::Queue) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("corgi-3d"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/gx.wgsl").into()),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("corgi-3d-layout"),
+            bind_group_layouts: &[],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("corgi-3d-pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<GpuVertex>() as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &wgpu::vertex_attr_array![0 => Float32x4, 1 => Float32x4],
+                }],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: wgpu::TextureFormat::Rgba8Unorm,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        let size = wgpu::Extent3d {
+            width: PIXELS_PER_LINE as u32,
+            height: SCANLINES as u32,
+            depth_or_array_layers: 1,
+        };
+        let color_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("corgi-3d-color"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let depth_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("corgi-3d-depth"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Depth32Float,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+
+        let staging_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("corgi-3d-readback"),
+            size: (size.width * size.height * 4) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        WgpuRenderer {
+            device,
+            queue,
+            pipeline,
+            color_texture,
+            depth_texture,
+            staging_buffer,
+            vertices: Vec::new(),
+            clear_color: 0,
+            clear_depth: 0,
+            readback: vec![0; (size.width * size.height) as usize],
+        }
+    }
+
+    /// Convert a packed ARGB8888 `clear_color` (the same format
+    /// `SoftwareRenderer::begin_frame` fills its framebuffer with) into the
+    /// 0.0-1.0 per-channel form wgpu's `LoadOp::Clear` expects
+    fn clear_color_wgpu(&self) -> wgpu::Color {
+        let c = self.clear_color;
+        wgpu::Color {
+            r: ((c >> 16) & 0xFF) as f64 / 255.0,
+            g: ((c >> 8) & 0xFF) as f64 / 255.0,
+            b: (c & 0xFF) as f64 / 255.0,
+            a: ((c >> 24) & 0xFF) as f64 / 255.0,
+        }
+    }
+}
+
+#[cfg(feature = "wgpu-renderer")]
+impl Renderer3D for WgpuRenderer {
+    fn begin_frame(&mut self, clear_color: u32, clear_depth: u32) {
+        self.vertices.clear();
+        self.clear_color = clear_color;
+        self.clear_depth = clear_depth;
+    }
+
+    fn submit_polygon(&mut self, poly: &Polygon, verts: &[Vertex], _disp3dcnt: &Disp3dCnt) {
+        // Fan-triangulate on upload, same convention as `SoftwareRenderer`
+        for i in 1..verts.len().saturating_sub(1) {
+            for v in [&verts[0], &verts[i], &verts[i + 1]] {
+                self.vertices.push(GpuVertex {
+                    position: [
+                        v.coords[0] as f32 / 4096.0,
+                        v.coords[1] as f32 / 4096.0,
+                        v.coords[2] as f32 / 4096.0,
+                        v.coords[3] as f32 / 4096.0,
+                    ],
+                    color: [
+                        v.colors[0] as f32 / 31.0,
+                        v.colors[1] as f32 / 31.0,
+                        v.colors[2] as f32 / 31.0,
+                        poly.attributes.alpha as f32 / 31.0,
+                    ],
+                });
+            }
+        }
+    }
+
+    fn finish_frame(&mut self) {
+        use wgpu::util::DeviceExt;
+
+        let vertex_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("corgi-3d-vbuf"),
+            contents: bytemuck::cast_slice(&self.vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        let color_view = self.color_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let depth_view = self.depth_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        // The NDS's CLEAR_DEPTH register holds a 24-bit depth value; rescale
+        // it to the 0.0-1.0 range wgpu's depth attachment expects.
+        let clear_depth = (self.clear_depth & 0x00FF_FFFF) as f32 / 0x00FF_FFFF as f32;
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("corgi-3d-encoder") });
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("corgi-3d-pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &color_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations { load: wgpu::LoadOp::Clear(self.clear_color_wgpu()), store: wgpu::StoreOp::Store },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &depth_view,
+                    depth_ops: Some(wgpu::Operations { load: wgpu::LoadOp::Clear(clear_depth), store: wgpu::StoreOp::Store }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            pass.set_pipeline(&self.pipeline);
+            pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+            pass.draw(0..self.vertices.len() as u32, 0..1);
+        }
+
+        let size = self.color_texture.size();
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture: &self.color_texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &self.staging_buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(size.width * 4),
+                    rows_per_image: Some(size.height),
+                },
+            },
+            size,
+        );
+        self.queue.submit(Some(encoder.finish()));
+
+        // Map the staging buffer and drain it synchronously: `Renderer3D` is
+        // a synchronous trait, so `finish_frame` has to block here rather
+        // than leave the copy pending for a later poll.
+        let slice = self.staging_buffer.slice(..);
+        let (tx, rx) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = tx.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        rx.recv().unwrap().expect("failed to map wgpu readback buffer");
+
+        {
+            let data = slice.get_mapped_range();
+            for (pixel, texel) in self.readback.iter_mut().zip(data.chunks_exact(4)) {
+                let (r, g, b, a) = (texel[0] as u32, texel[1] as u32, texel[2] as u32, texel[3] as u32);
+                *pixel = (a << 24) | (r << 16) | (g << 8) | b;
+            }
+        }
+        self.staging_buffer.unmap();
+    }
+
+    fn framebuffer(&self) -> &[u32] {
+        &self.readback
+    }
+}
@@ -0,0 +1,157 @@
+/// Window-space touch handling and on-screen controls
+///
+/// `touchscreen.rs` already converts lower-screen pixel coordinates to the
+/// 12-bit ADC values the DS TSC expects via the firmware calibration; this
+/// module is the layer above it, translating a frontend's raw window-space
+/// touch/click coordinates into those lower-screen pixels (accounting for
+/// where the frontend puts the two screens and how it scales them) and
+/// hit-testing an optional on-screen D-pad/button overlay for touch-only
+/// targets, the same motivation behind other emulators' Android/touch ports.
+use crate::corgi_core::DSKey;
+use crate::memconsts::{PIXELS_PER_LINE, SCANLINES};
+
+/// Where the frontend places the upper and lower screens relative to each
+/// other (the scale lives in `TouchMapper::scale`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScreenLayout {
+    /// Upper screen above the lower screen (the DS's native layout)
+    TopBottom,
+    /// Lower screen above the upper screen
+    BottomTop,
+    /// Screens side by side, upper on the left
+    SideBySide,
+    /// Only the lower screen is shown, e.g. a phone in portrait with the
+    /// upper screen hidden or on a separate view
+    LowerOnly,
+}
+
+/// Translates window-space coordinates into lower-screen pixel coordinates
+/// (0..256, 0..192), given how the frontend has arranged and scaled the two
+/// screens
+#[derive(Debug, Clone, Copy)]
+pub struct TouchMapper {
+    pub layout: ScreenLayout,
+    /// Pixels-per-DS-pixel scale factor the frontend is rendering at
+    pub scale: f32,
+}
+
+impl TouchMapper {
+    pub fn new(layout: ScreenLayout, scale: f32) -> Self {
+        TouchMapper { layout, scale }
+    }
+
+    /// Map a window-space point to lower-screen pixel coordinates, or
+    /// `None` if the point falls outside the lower screen's area
+    pub fn map(&self, x: i32, y: i32) -> Option<(i32, i32)> {
+        let scale = if self.scale > 0.0 { self.scale } else { 1.0 };
+        let screen_w = (PIXELS_PER_LINE as f32 * scale) as i32;
+        let screen_h = (SCANLINES as f32 * scale) as i32;
+
+        let (lx, ly) = match self.layout {
+            ScreenLayout::TopBottom => (x, y - screen_h),
+            ScreenLayout::BottomTop => (x, y),
+            ScreenLayout::SideBySide => (x - screen_w, y),
+            ScreenLayout::LowerOnly => (x, y),
+        };
+
+        if lx < 0 || ly < 0 || lx >= screen_w || ly >= screen_h {
+            return None;
+        }
+
+        Some(((lx as f32 / scale) as i32, (ly as f32 / scale) as i32))
+    }
+}
+
+impl Default for TouchMapper {
+    fn default() -> Self {
+        TouchMapper::new(ScreenLayout::TopBottom, 1.0)
+    }
+}
+
+/// One hit-testable region of an on-screen control overlay
+#[derive(Debug, Clone, Copy)]
+pub struct OnScreenButton {
+    pub key: DSKey,
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl OnScreenButton {
+    fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+}
+
+/// On-screen D-pad/face-button/shoulder overlay: emits the same `DSKey`
+/// actions the keyboard and gamepad input layers do, so a touch-only
+/// frontend needs no separate input path
+#[derive(Debug, Clone)]
+pub struct OnScreenControls {
+    buttons: Vec<OnScreenButton>,
+    /// Buttons currently held down by an active touch, keyed by touch id so
+    /// multiple simultaneous touches (D-pad + a face button) work
+    held: Vec<(u32, DSKey)>,
+}
+
+impl OnScreenControls {
+    /// Lay out a standard D-pad + face buttons + shoulders overlay inside a
+    /// `viewport_width` x `viewport_height` display area (D-pad and face
+    /// buttons along its bottom edge, shoulders in its top corners)
+    pub fn standard_layout(viewport_width: i32, viewport_height: i32) -> Self {
+        let button_size = viewport_width / 10;
+        let pad_y = viewport_height - button_size * 3;
+
+        let dpad_x = button_size;
+        let buttons = vec![
+            OnScreenButton { key: DSKey::Up, x: dpad_x + button_size, y: pad_y, width: button_size, height: button_size },
+            OnScreenButton { key: DSKey::Down, x: dpad_x + button_size, y: pad_y + button_size * 2, width: button_size, height: button_size },
+            OnScreenButton { key: DSKey::Left, x: dpad_x, y: pad_y + button_size, width: button_size, height: button_size },
+            OnScreenButton { key: DSKey::Right, x: dpad_x + button_size * 2, y: pad_y + button_size, width: button_size, height: button_size },
+            OnScreenButton { key: DSKey::A, x: viewport_width - button_size * 2, y: pad_y + button_size, width: button_size, height: button_size },
+            OnScreenButton { key: DSKey::B, x: viewport_width - button_size * 3, y: pad_y + button_size * 2, width: button_size, height: button_size },
+            OnScreenButton { key: DSKey::X, x: viewport_width - button_size * 3, y: pad_y, width: button_size, height: button_size },
+            OnScreenButton { key: DSKey::Y, x: viewport_width - button_size * 4, y: pad_y + button_size, width: button_size, height: button_size },
+            OnScreenButton { key: DSKey::L, x: 0, y: 0, width: button_size, height: button_size },
+            OnScreenButton { key: DSKey::R, x: viewport_width - button_size, y: 0, width: button_size, height: button_size },
+            OnScreenButton { key: DSKey::Start, x: viewport_width / 2 + button_size / 2, y: pad_y - button_size, width: button_size, height: button_size / 2 },
+            OnScreenButton { key: DSKey::Select, x: viewport_width / 2 - button_size * 3 / 2, y: pad_y - button_size, width: button_size, height: button_size / 2 },
+        ];
+
+        OnScreenControls { buttons, held: Vec::new() }
+    }
+
+    /// A touch identified by `touch_id` landed at `(x, y)`: hit-test the
+    /// overlay and report the button it should press, if any
+    pub fn touch_down(&mut self, touch_id: u32, x: i32, y: i32) -> Option<DSKey> {
+        let key = self.buttons.iter().find(|b| b.contains(x, y)).map(|b| b.key)?;
+        self.held.push((touch_id, key));
+        Some(key)
+    }
+
+    /// The same touch moved to `(x, y)`: if it left its button's region (or
+    /// entered a different one), release the old button and press the new
+    /// one. Returns `(released, pressed)`.
+    pub fn touch_move(&mut self, touch_id: u32, x: i32, y: i32) -> (Option<DSKey>, Option<DSKey>) {
+        let current = self.held.iter().position(|&(id, _)| id == touch_id).map(|i| self.held[i].1);
+        let hit = self.buttons.iter().find(|b| b.contains(x, y)).map(|b| b.key);
+
+        if current == hit {
+            return (None, None);
+        }
+
+        let released = self.touch_up(touch_id);
+        if let Some(key) = hit {
+            self.held.push((touch_id, key));
+        }
+        (released, hit)
+    }
+
+    /// The touch identified by `touch_id` was released: report the button
+    /// it had been holding, if any
+    pub fn touch_up(&mut self, touch_id: u32) -> Option<DSKey> {
+        let index = self.held.iter().position(|&(id, _)| id == touch_id)?;
+        Some(self.held.remove(index).1)
+    }
+}
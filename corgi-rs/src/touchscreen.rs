@@ -1,5 +1,6 @@
 /// Touchscreen controller for Nintendo DS
 /// Implements ADS7843-compatible SPI touchscreen interface
+use crate::firmware::TouchCalibration;
 
 /// Touchscreen controller
 /// Communicates via SPI protocol (ADS7843 compatible)
@@ -18,6 +19,10 @@ pub struct TouchScreen {
 
     /// Pen down state
     pen_down: bool,
+
+    /// Firmware-provided calibration used to convert between screen pixels
+    /// and ADC readings
+    calibration: TouchCalibration,
 }
 
 impl TouchScreen {
@@ -30,9 +35,15 @@ impl TouchScreen {
             press_x: 0,
             press_y: 0,
             pen_down: false,
+            calibration: TouchCalibration::default(),
         }
     }
 
+    /// Install the firmware's touchscreen calibration
+    pub fn set_calibration(&mut self, calibration: TouchCalibration) {
+        self.calibration = calibration;
+    }
+
     /// Power on touchscreen
     pub fn power_on(&mut self) -> Result<(), String> {
         self.pen_down = false;
@@ -42,11 +53,28 @@ impl TouchScreen {
     }
 
     /// Handle press event at screen coordinates
+    /// Converts screen pixels to ADC readings via the firmware calibration,
+    /// inverting the `screen = f(adc)` mapping used by [`Self::adc_to_pixel_x`]
+    /// and [`Self::adc_to_pixel_y`].
     pub fn press_event(&mut self, x: i32, y: i32) {
-        // Convert screen coordinates to ADC values (0-4095)
-        // Screen is 256x192 pixels, touchscreen is ~4096 steps each direction
-        self.press_x = ((x as u32 * 4096) / 256) as u16;
-        self.press_y = ((y as u32 * 4096) / 192) as u16;
+        let c = &self.calibration;
+
+        let scr_x_span = c.scr_x2 - c.scr_x1;
+        self.press_x = if scr_x_span != 0 {
+            (((x - c.scr_x1) * (c.adc_x2 - c.adc_x1)) / scr_x_span + c.adc_x1)
+                .clamp(0, 4095) as u16
+        } else {
+            ((x as u32 * 4096) / 256) as u16
+        };
+
+        let scr_y_span = c.scr_y2 - c.scr_y1;
+        self.press_y = if scr_y_span != 0 {
+            (((y - c.scr_y1) * (c.adc_y2 - c.adc_y1)) / scr_y_span + c.adc_y1)
+                .clamp(0, 4095) as u16
+        } else {
+            ((y as u32 * 4096) / 192) as u16
+        };
+
         self.pen_down = true;
     }
 
@@ -170,24 +198,44 @@ impl TouchScreen {
         self.pen_down = down;
     }
 
-    /// Convert ADC value to screen pixel
-    pub fn adc_to_pixel_x(adc: u16) -> i32 {
-        ((adc as i32 * 256) / 4096).min(255).max(0)
+    /// Convert ADC value to screen pixel X using the firmware calibration
+    pub fn adc_to_pixel_x(&self, adc: u16) -> i32 {
+        let c = &self.calibration;
+        let adc_span = c.adc_x2 - c.adc_x1;
+        if adc_span == 0 {
+            return ((adc as i32 * 256) / 4096).clamp(0, 255);
+        }
+        (((adc as i32 - c.adc_x1) * (c.scr_x2 - c.scr_x1)) / adc_span + c.scr_x1).clamp(0, 255)
     }
 
-    /// Convert ADC value to screen pixel Y
-    pub fn adc_to_pixel_y(adc: u16) -> i32 {
-        ((adc as i32 * 192) / 4096).min(191).max(0)
+    /// Convert ADC value to screen pixel Y using the firmware calibration
+    pub fn adc_to_pixel_y(&self, adc: u16) -> i32 {
+        let c = &self.calibration;
+        let adc_span = c.adc_y2 - c.adc_y1;
+        if adc_span == 0 {
+            return ((adc as i32 * 192) / 4096).clamp(0, 191);
+        }
+        (((adc as i32 - c.adc_y1) * (c.scr_y2 - c.scr_y1)) / adc_span + c.scr_y1).clamp(0, 191)
     }
 
-    /// Convert pixel to ADC value X
-    pub fn pixel_to_adc_x(pixel: i32) -> u16 {
-        ((pixel as u32 * 4096) / 256) as u16
+    /// Convert screen pixel X to an ADC value using the firmware calibration
+    pub fn pixel_to_adc_x(&self, pixel: i32) -> u16 {
+        let c = &self.calibration;
+        let scr_span = c.scr_x2 - c.scr_x1;
+        if scr_span == 0 {
+            return ((pixel as u32 * 4096) / 256) as u16;
+        }
+        (((pixel - c.scr_x1) * (c.adc_x2 - c.adc_x1)) / scr_span + c.adc_x1).clamp(0, 4095) as u16
     }
 
-    /// Convert pixel to ADC value Y
-    pub fn pixel_to_adc_y(pixel: i32) -> u16 {
-        ((pixel as u32 * 4096) / 192) as u16
+    /// Convert screen pixel Y to an ADC value using the firmware calibration
+    pub fn pixel_to_adc_y(&self, pixel: i32) -> u16 {
+        let c = &self.calibration;
+        let scr_span = c.scr_y2 - c.scr_y1;
+        if scr_span == 0 {
+            return ((pixel as u32 * 4096) / 192) as u16;
+        }
+        (((pixel - c.scr_y1) * (c.adc_y2 - c.adc_y1)) / scr_span + c.adc_y1).clamp(0, 4095) as u16
     }
 }
 